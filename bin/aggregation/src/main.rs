@@ -0,0 +1,28 @@
+#![no_main]
+zkm_zkvm::entrypoint!(main);
+
+use guest_executor::io::{AggregationInput, AggregationPublicValues, BlockPublicValues};
+use sha2::{Digest, Sha256};
+
+pub fn main() {
+    // Read the input.
+    let input = zkm_zkvm::io::read_vec();
+    let input = bincode::deserialize::<AggregationInput>(&input).unwrap();
+
+    // Verify the block proofs.
+    let blocks = input
+        .public_values
+        .iter()
+        .map(|public_values| {
+            let public_values_digest = Sha256::digest(public_values);
+            zkm_zkvm::lib::verify::verify_zkm_proof(&input.vkey_hash, &public_values_digest.into());
+
+            BlockPublicValues::decode(public_values).expect("invalid block public values")
+        })
+        .collect::<Vec<_>>();
+
+    // Check the linkage between the blocks and commit the aggregated public values.
+    let public_values = AggregationPublicValues::aggregate(input.vkey_hash, &blocks)
+        .expect("failed to aggregate the block proofs");
+    zkm_zkvm::io::commit(&public_values);
+}
@@ -2,4 +2,5 @@ use zkm_build::build_program;
 
 fn main() {
     build_program("../guest");
+    build_program("../guest-op");
 }
@@ -1,12 +1,12 @@
 use std::{fs, path::PathBuf};
 
 use alloy_chains::Chain;
-use alloy_provider::{network::AnyNetwork, Provider, RootProvider};
+use alloy_provider::{network::AnyNetwork, Provider};
 use clap::Parser;
-use host_executor::Config;
+use host_executor::{Config, FetchArgs, ProofKind, WitnessSource, DEFAULT_RPC_CACHE_SIZE};
 use primitives::genesis::Genesis;
+use provider::create_provider;
 use url::Url;
-use zkm_sdk::ZKMProofKind;
 
 /// The arguments for the cli.
 #[derive(Debug, Clone, Parser)]
@@ -20,6 +20,15 @@ pub struct Args {
     #[clap(long)]
     pub rpc_url: Option<Url>,
 
+    /// The rpc urls failed over to, in order, when the rpc_url fails.
+    #[clap(long, env, value_delimiter = ',')]
+    pub fallback_rpc_urls: Vec<Url>,
+
+    /// Additional rpc urls whose responses to the proof, code and block requests are compared
+    /// with the ones of the rpc_url, the response of the majority being used.
+    #[clap(long, env, value_delimiter = ',')]
+    pub quorum_rpc_urls: Vec<Url>,
+
     /// The chain ID. If not provided, requires the rpc_url argument to be provided.
     #[clap(long, env)]
     pub chain_id: Option<u64>,
@@ -28,6 +37,9 @@ pub struct Args {
     #[clap(long, env)]
     pub genesis_path: Option<PathBuf>,
 
+    #[clap(flatten)]
+    pub fetch: FetchArgs,
+
     /// The maximum number of concurrent executions.
     #[clap(long, env, default_value_t = 1)]
     pub max_concurrent_executions: usize,
@@ -40,6 +52,35 @@ pub struct Args {
     #[clap(long, env, default_value_t = false)]
     pub prove: bool,
 
+    /// The kind of proof to generate.
+    #[clap(long, env, value_enum, default_value_t = ProofKind::Compressed)]
+    pub proof_kind: ProofKind,
+
+    /// Optional path to the directory where the proving and verifying keys are cached, keyed by
+    /// the ELF SHA-256.
+    #[clap(long, env)]
+    pub key_cache_dir: Option<PathBuf>,
+
+    /// Optional `s3://<bucket>/<prefix>` URL of an S3-compatible object storage to save the
+    /// generated client inputs to, so that they can be proven on other machines. The credentials
+    /// and endpoint are read from the `AWS_*` env vars.
+    #[clap(long, env)]
+    pub input_store_url: Option<Url>,
+
+    /// The maximum number of headers, bytecodes and account proofs cached across the executed
+    /// blocks, 0 to disable the cache.
+    #[clap(long, env, default_value_t = DEFAULT_RPC_CACHE_SIZE)]
+    pub rpc_cache_size: usize,
+
+    /// Where the witness of the state accessed by the blocks is fetched from.
+    #[clap(long, env, value_enum, default_value_t = WitnessSource::Proofs)]
+    pub witness_source: WitnessSource,
+
+    /// Whether to compare the touched accounts against the RPC state when the computed state
+    /// root mismatches, and log the accounts and slots whose values diverge.
+    #[clap(long, env)]
+    pub diagnose_state_mismatch: bool,
+
     /// PagerDuty integration key.
     #[clap(long, env)]
     pub pager_duty_integration_key: Option<String>,
@@ -67,7 +108,7 @@ impl Args {
             }
             (Some(rpc_url), None) => {
                 // We can find out about chain ID from RPC.
-                let provider = RootProvider::<AnyNetwork>::new_http(rpc_url.clone());
+                let provider = create_provider::<AnyNetwork>(rpc_url.clone()).await?;
 
                 (Some(rpc_url), provider.get_chain_id().await?)
             }
@@ -91,9 +132,19 @@ impl Args {
             chain,
             genesis,
             rpc_url,
+            fallback_rpc_urls: self.fallback_rpc_urls.clone(),
+            retry_config: self.fetch.retry_config(),
             cache_dir: None,
+            cache_max_size: None,
+            input_store_url: self.input_store_url.clone(),
+            compress_input_cache: false,
+            key_cache_dir: self.key_cache_dir.clone(),
+            fetch_config: self.fetch.fetch_config(),
+            rpc_cache_size: self.rpc_cache_size,
+            witness_source: self.witness_source,
+            diagnose_state_mismatch: self.diagnose_state_mismatch,
             custom_beneficiary: None,
-            prove_mode: self.prove.then_some(ZKMProofKind::Compressed),
+            prove_mode: self.prove.then_some(self.proof_kind.into()),
             opcode_tracking: false,
             debug_rpc_url: None,
         };
@@ -1,16 +1,21 @@
-use std::sync::{
-    atomic::{AtomicBool, Ordering},
-    Arc,
+use std::{
+    future::Future,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
 };
 
-use alloy_provider::{network::Ethereum, Provider};
+use alloy_provider::{network::Ethereum, Network, Provider, RootProvider};
 use clap::Parser;
 use cli::Args;
 use host_executor::{
-    alerting::AlertingClient, create_eth_block_execution_strategy_factory, BlockExecutor,
-    EthExecutorComponents, ExecutorComponents, FullExecutor,
+    alerting::AlertingClient, create_eth_block_execution_strategy_factory,
+    create_op_block_execution_strategy_factory, BlockExecutor, Config, EthExecutorComponents,
+    ExecutorComponents, FullExecutor, OpExecutorComponents,
 };
-use provider::create_provider;
+use op_alloy_network::Optimism;
+use provider::{create_failover_provider, create_quorum_provider};
 use tokio::{sync::Semaphore, task};
 use tracing::{error, info, instrument, warn};
 use tracing_subscriber::util::SubscriberInitExt;
@@ -44,31 +49,99 @@ async fn main() -> eyre::Result<()> {
     let config = args.as_config().await?;
     info!("args: {:?}", args);
 
-    let elf = include_elf!("reth").to_vec();
-    let block_execution_strategy_factory =
-        create_eth_block_execution_strategy_factory(&config.genesis, None);
-
     tracing::info!("first block number: {}", args.block_number);
 
-    let http_provider = create_provider(config.rpc_url.clone().unwrap());
     let alerting_client =
-        args.pager_duty_integration_key.map(|key| Arc::new(AlertingClient::new(key)));
+        args.pager_duty_integration_key.clone().map(|key| Arc::new(AlertingClient::new(key)));
 
     let prover_client = Arc::new(ProverClient::new());
+    let execution_retries = args.execution_retries;
+
+    if config.genesis.is_optimism() {
+        let elf = include_elf!("reth-op").to_vec();
+        let block_execution_strategy_factory =
+            create_op_block_execution_strategy_factory(&config.genesis);
+        let http_provider = create_http_provider::<Optimism>(&config, &args).await?;
+
+        let executor = Arc::new(
+            FullExecutor::<OpExecutorComponents<_, _>, _>::try_new(
+                http_provider.clone(),
+                http_provider,
+                elf,
+                block_execution_strategy_factory,
+                prover_client,
+                (),
+                config,
+            )
+            .await?,
+        );
+
+        run(&args, alerting_client, |block_number| {
+            process_block(block_number, executor.clone(), execution_retries)
+        })
+        .await
+    } else {
+        let elf = include_elf!("reth").to_vec();
+        let block_execution_strategy_factory =
+            create_eth_block_execution_strategy_factory(&config.genesis, None);
+        let http_provider = create_http_provider::<Ethereum>(&config, &args).await?;
+
+        let executor = Arc::new(
+            FullExecutor::<EthExecutorComponents<_, _>, _>::try_new(
+                http_provider.clone(),
+                http_provider,
+                elf,
+                block_execution_strategy_factory,
+                prover_client,
+                (),
+                config,
+            )
+            .await?,
+        );
+
+        run(&args, alerting_client, |block_number| {
+            process_block(block_number, executor.clone(), execution_retries)
+        })
+        .await
+    }
+}
 
-    let executor = Arc::new(
-        FullExecutor::<EthExecutorComponents<_, _>, _>::try_new(
-            http_provider.clone(),
-            http_provider,
-            elf,
-            block_execution_strategy_factory,
-            prover_client,
-            (),
-            config,
+/// Creates the provider of the RPC, failing over to the fallback RPCs, or checked against the
+/// quorum RPCs if any.
+async fn create_http_provider<N: Network>(
+    config: &Config,
+    args: &Args,
+) -> eyre::Result<RootProvider<N>> {
+    let rpc_url = config.rpc_url.clone().unwrap();
+
+    let provider = if args.quorum_rpc_urls.is_empty() {
+        create_failover_provider(
+            std::iter::once(rpc_url).chain(config.fallback_rpc_urls.clone()).collect(),
+            &config.retry_config,
         )
-        .await?,
-    );
+        .await?
+    } else {
+        create_quorum_provider(
+            std::iter::once(rpc_url).chain(args.quorum_rpc_urls.clone()).collect(),
+            &config.retry_config,
+        )
+        .await?
+    };
 
+    Ok(provider)
+}
+
+/// Processes blocks continuously starting from `args.block_number`, spawning one task per block
+/// with at most `args.max_concurrent_executions` running at the same time.
+async fn run<F, Fut>(
+    args: &Args,
+    alerting_client: Option<Arc<AlertingClient>>,
+    process: F,
+) -> eyre::Result<()>
+where
+    F: Fn(u64) -> Fut,
+    Fut: Future<Output = eyre::Result<()>> + Send + 'static,
+{
     let concurrent_executions_semaphore = Arc::new(Semaphore::new(args.max_concurrent_executions));
 
     let failed = Arc::new(AtomicBool::new(false));
@@ -79,13 +152,13 @@ async fn main() -> eyre::Result<()> {
     loop {
         info!("process block: {:?}", block_number);
 
-        let executor = executor.clone();
         let alerting_client = alerting_client.clone();
         let permit = concurrent_executions_semaphore.clone().acquire_owned().await?;
         let flag = Arc::clone(&failed);
+        let processing = process(block_number);
 
         task::spawn(async move {
-            match process_block(block_number, executor, args.execution_retries).await {
+            match processing.await {
                 Ok(_) => {
                     info!("Successfully processed block {block_number}");
                 }
@@ -119,8 +192,8 @@ async fn process_block<C, P>(
     max_retries: usize,
 ) -> eyre::Result<()>
 where
-    C: ExecutorComponents<Network = Ethereum>,
-    P: Provider<Ethereum> + Clone + std::fmt::Debug,
+    C: ExecutorComponents,
+    P: Provider<C::Network> + Clone + std::fmt::Debug,
 {
     // Wait for the block to be available in the HTTP provider
     let mut retry_count = 0;
@@ -1,9 +1,10 @@
+use std::path::PathBuf;
+
 use alloy_chains::Chain;
 use clap::Parser;
-use host_executor::Config;
+use host_executor::{Config, FetchArgs, ProofKind, WitnessSource, DEFAULT_RPC_CACHE_SIZE};
 use primitives::genesis::Genesis;
 use url::Url;
-use zkm_sdk::ZKMProofKind;
 
 /// The arguments for the cli.
 #[derive(Debug, Clone, Parser)]
@@ -12,7 +13,17 @@ pub struct Args {
     #[clap(long, env)]
     pub http_rpc_url: Url,
 
-    /// The WS rpc url used to fetch data about the block.
+    /// The HTTP rpc urls failed over to, in order, when the http_rpc_url fails.
+    #[clap(long, env, value_delimiter = ',')]
+    pub fallback_http_rpc_urls: Vec<Url>,
+
+    /// Additional HTTP rpc urls whose responses to the proof, code and block requests are
+    /// compared with the ones of the http_rpc_url, the response of the majority being used.
+    #[clap(long, env, value_delimiter = ',')]
+    pub quorum_http_rpc_urls: Vec<Url>,
+
+    /// The WS rpc url used to subscribe to the new blocks, an IPC socket `ipc://` URL can also
+    /// be used.
     #[clap(long, env)]
     pub ws_rpc_url: Url,
 
@@ -20,10 +31,17 @@ pub struct Args {
     #[clap(long, env)]
     pub debug_http_rpc_url: Url,
 
+    #[clap(flatten)]
+    pub fetch: FetchArgs,
+
     /// Whether to generate a proof or just execute the block.
     #[clap(long)]
     pub execute_only: bool,
 
+    /// The kind of proof to generate.
+    #[clap(long, env, value_enum, default_value_t = ProofKind::Compressed)]
+    pub proof_kind: ProofKind,
+
     /// The interval at which to execute blocks.
     #[clap(long, default_value_t = 100)]
     pub block_interval: u64,
@@ -40,6 +58,20 @@ pub struct Args {
     #[clap(long, default_value_t = 1)]
     pub eth_proofs_cluster_id: u64,
 
+    /// Where the witness of the state accessed by the blocks is fetched from.
+    #[clap(long, env, value_enum, default_value_t = WitnessSource::Proofs)]
+    pub witness_source: WitnessSource,
+
+    /// Whether to compare the touched accounts against the RPC state when the computed state
+    /// root mismatches, and log the accounts and slots whose values diverge.
+    #[clap(long, env)]
+    pub diagnose_state_mismatch: bool,
+
+    /// Optional path to the directory where the proving and verifying keys are cached, keyed by
+    /// the ELF SHA-256.
+    #[clap(long, env)]
+    pub key_cache_dir: Option<PathBuf>,
+
     /// PagerDuty integration key.
     #[clap(long, env)]
     pub pager_duty_integration_key: Option<String>,
@@ -55,10 +87,20 @@ impl Args {
             chain: Chain::mainnet(),
             genesis: Genesis::Mainnet,
             rpc_url: Some(self.http_rpc_url.clone()),
+            fallback_rpc_urls: self.fallback_http_rpc_urls.clone(),
             debug_rpc_url: Some(self.debug_http_rpc_url.clone()),
+            retry_config: self.fetch.retry_config(),
             cache_dir: None,
+            cache_max_size: None,
+            input_store_url: None,
+            compress_input_cache: false,
+            key_cache_dir: self.key_cache_dir.clone(),
+            fetch_config: self.fetch.fetch_config(),
+            rpc_cache_size: DEFAULT_RPC_CACHE_SIZE,
+            witness_source: self.witness_source,
+            diagnose_state_mismatch: self.diagnose_state_mismatch,
             custom_beneficiary: None,
-            prove_mode: (!self.execute_only).then_some(ZKMProofKind::Compressed),
+            prove_mode: (!self.execute_only).then_some(self.proof_kind.into()),
             opcode_tracking: false,
         };
 
@@ -2,7 +2,7 @@ use std::time::Duration;
 
 use base64::{engine::general_purpose::STANDARD, Engine};
 use eyre::eyre;
-use host_executor::ExecutionHooks;
+use host_executor::{ExecutionHooks, ProofProgram};
 use reqwest_middleware::{ClientBuilder, ClientWithMiddleware};
 use reqwest_retry::{policies::ExponentialBackoff, RetryTransientMiddleware};
 use tracing::error;
@@ -136,6 +136,7 @@ impl ExecutionHooks for EthProofsClient {
     async fn on_proving_end(
         &self,
         block_number: u64,
+        _program: ProofProgram,
         proof_bytes: &[u8],
         _public_values_bytes: &[u8],
         _zkm_version: &str,
@@ -1,6 +1,6 @@
 use std::{env, sync::Arc};
 
-use alloy_provider::{Provider, ProviderBuilder, WsConnect};
+use alloy_provider::{network::Ethereum, Provider};
 use clap::Parser;
 use cli::Args;
 use eth_proofs::EthProofsClient;
@@ -9,7 +9,7 @@ use host_executor::{
     alerting::AlertingClient, create_eth_block_execution_strategy_factory, BlockExecutor,
     EthExecutorComponents, FullExecutor,
 };
-use provider::create_provider;
+use provider::{create_failover_provider, create_quorum_provider, create_subscription_provider};
 use tracing::{error, info};
 use tracing_subscriber::{fmt, layer::SubscriberExt, util::SubscriberInitExt, EnvFilter};
 use zkm_sdk::{include_elf, ProverClient};
@@ -54,10 +54,22 @@ async fn main() -> eyre::Result<()> {
     );
     let alerting_client = args.pager_duty_integration_key.map(AlertingClient::new);
 
-    let ws = WsConnect::new(args.ws_rpc_url);
-    let ws_provider = ProviderBuilder::new().connect_ws(ws).await?;
-    let http_provider = create_provider(args.http_rpc_url);
-    let debug_http_provider = create_provider(args.debug_http_rpc_url);
+    let ws_provider = create_subscription_provider::<Ethereum>(args.ws_rpc_url).await?;
+    let http_provider = if args.quorum_http_rpc_urls.is_empty() {
+        create_failover_provider(
+            std::iter::once(args.http_rpc_url).chain(config.fallback_rpc_urls.clone()).collect(),
+            &config.retry_config,
+        )
+        .await?
+    } else {
+        create_quorum_provider(
+            std::iter::once(args.http_rpc_url).chain(args.quorum_http_rpc_urls).collect(),
+            &config.retry_config,
+        )
+        .await?
+    };
+    let debug_http_provider =
+        create_failover_provider(vec![args.debug_http_rpc_url], &config.retry_config).await?;
 
     // Subscribe to block headers.
     let subscription = ws_provider.subscribe_blocks().await?;
@@ -88,7 +100,7 @@ async fn main() -> eyre::Result<()> {
         // Wait for the block to be avaliable in the HTTP provider
         executor.wait_for_block(header.number).await?;
 
-        if let Err(err) = executor.execute(header.number).await {
+        if let Err(err) = executor.execute_block_hash(header.number, header.hash).await {
             let error_message = format!("Error handling block {}: {err}", header.number);
             error!(error_message);
 
@@ -0,0 +1,14 @@
+#![no_main]
+zkm_zkvm::entrypoint!(main);
+
+use guest_executor::verify_op_block;
+
+pub fn main() {
+    // Read the input.
+    let input = zkm_zkvm::io::read_vec();
+
+    let public_values = verify_op_block(&input);
+
+    // Commit the public values.
+    zkm_zkvm::io::commit(&public_values);
+}
@@ -7,8 +7,8 @@ pub fn main() {
     // Read the input.
     let input = zkm_zkvm::io::read_vec();
 
-    let (block_hash, _, _) = verify_block(&input);
+    let public_values = verify_block(&input);
 
-    // Commit the block hash.
-    zkm_zkvm::io::commit(&block_hash);
+    // Commit the public values.
+    zkm_zkvm::io::commit(&public_values);
 }
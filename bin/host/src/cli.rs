@@ -1,24 +1,67 @@
-use std::{fs, path::PathBuf};
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
 
 use alloy_chains::Chain;
-use alloy_primitives::Address;
-use alloy_provider::{network::AnyNetwork, Provider, RootProvider};
-use clap::Parser;
-use host_executor::Config;
+use alloy_primitives::{Address, B256};
+use alloy_provider::{network::AnyNetwork, Provider};
+use clap::{Args, Parser, Subcommand};
+use host_executor::{Config, FetchArgs, ProofKind, WitnessSource, DEFAULT_RPC_CACHE_SIZE};
 use primitives::genesis::Genesis;
+use provider::create_provider;
 use url::Url;
 use zkm_sdk::ZKMProofKind;
 
 /// The arguments for the host executable.
 #[derive(Debug, Clone, Parser)]
+#[clap(args_conflicts_with_subcommands = true, subcommand_negates_reqs = true)]
 pub struct HostArgs {
+    #[clap(subcommand)]
+    pub command: Option<HostCommand>,
+
     /// The block number of the block to execute.
-    #[clap(long)]
-    pub block_number: u64,
+    #[clap(
+        long,
+        required_unless_present = "from_block",
+        conflicts_with_all = ["from_block", "to_block"]
+    )]
+    pub block_number: Option<u64>,
+
+    /// The hash of the block to execute, to pin it when it may be reorged. The block is otherwise
+    /// the canonical one at the time of the execution. Offline, it picks the cached input when
+    /// the inputs of several forks of the block are cached.
+    #[clap(long, requires = "block_number")]
+    pub block_hash: Option<B256>,
+
+    /// The first block of the range to execute.
+    #[clap(long, requires = "to_block")]
+    pub from_block: Option<u64>,
+
+    /// The last block of the range to execute (inclusive).
+    #[clap(long, requires = "from_block")]
+    pub to_block: Option<u64>,
+
+    /// Whether to execute the whole block range in a single guest run, instead of one run per
+    /// block.
+    #[clap(long, requires = "from_block")]
+    pub batch: bool,
+
+    /// Whether to aggregate the proofs of the blocks of the range into a single proof.
+    #[clap(long, requires_all = ["from_block", "prove"], conflicts_with = "batch")]
+    pub aggregate: bool,
+
+    /// Whether to run the client executor natively on the host instead of in the zkVM, to
+    /// quickly check the client inputs before proving them.
+    #[clap(long, conflicts_with_all = ["prove", "aggregate"])]
+    pub native: bool,
 
     #[clap(flatten)]
     pub provider: ProviderArgs,
 
+    #[clap(flatten)]
+    pub fetch: FetchArgs,
+
     /// The path to the genesis json file to use for the execution.
     #[clap(long)]
     pub genesis_path: Option<PathBuf>,
@@ -31,11 +74,60 @@ pub struct HostArgs {
     #[clap(long)]
     pub prove: bool,
 
+    /// The kind of proof to generate. When aggregating, the block proofs are always compressed
+    /// and this is the kind of the aggregation proof.
+    #[clap(long, value_enum, default_value_t = ProofKind::Compressed)]
+    pub proof_kind: ProofKind,
+
     /// Optional path to the directory containing cached client input. A new cache file will be
     /// created from RPC data if it doesn't already exist.
     #[clap(long)]
     pub cache_dir: Option<PathBuf>,
 
+    /// The maximum size of the inputs in the cache dir in bytes, beyond which the least recently
+    /// used ones are evicted.
+    #[clap(long)]
+    pub cache_max_size: Option<u64>,
+
+    /// Optional `s3://<bucket>/<prefix>` URL of an S3-compatible object storage to cache the
+    /// client inputs in, instead of the cache dir. The credentials and endpoint are read from the
+    /// `AWS_*` env vars.
+    #[clap(long)]
+    pub input_store_url: Option<Url>,
+
+    /// Whether to compress the cached client inputs with zstd.
+    #[clap(long)]
+    pub compress_input_cache: bool,
+
+    /// Optional path to the directory where the proving and verifying keys are cached, keyed by
+    /// the ELF SHA-256.
+    #[clap(long)]
+    pub key_cache_dir: Option<PathBuf>,
+
+    /// The maximum number of headers, bytecodes and account proofs cached across the executed
+    /// blocks, 0 to disable the cache.
+    #[clap(long, default_value_t = DEFAULT_RPC_CACHE_SIZE)]
+    pub rpc_cache_size: usize,
+
+    /// Where the witness of the state accessed by the blocks is fetched from. `auto` uses
+    /// `debug_executionWitness` if the debug RPC supports it, and `eth_getProof` otherwise.
+    #[clap(long, value_enum, default_value_t = WitnessSource::Proofs)]
+    pub witness_source: WitnessSource,
+
+    /// Whether to compare the touched accounts against the RPC state when the computed state
+    /// root mismatches, and log the accounts and slots whose values diverge.
+    #[clap(long)]
+    pub diagnose_state_mismatch: bool,
+
+    /// The directory where the generated proofs are persisted.
+    #[clap(long, default_value = "proofs")]
+    pub proof_dir: PathBuf,
+
+    /// Optional directory where the Groth16 and PLONK proofs are exported as Solidity fixtures,
+    /// consumable by Foundry tests of an on-chain verifier contract.
+    #[clap(long)]
+    pub fixture_dir: Option<PathBuf>,
+
     /// The path to the CSV file containing the execution data.
     #[clap(long, default_value = "report.csv")]
     pub report_path: PathBuf,
@@ -48,7 +140,56 @@ pub struct HostArgs {
     pub opcode_tracking: bool,
 }
 
+/// The subcommands of the host executable.
+#[derive(Debug, Clone, Subcommand)]
+pub enum HostCommand {
+    /// Verifies a persisted proof offline.
+    Verify(VerifyArgs),
+}
+
+/// The arguments for the `verify` subcommand.
+#[derive(Debug, Clone, Args)]
+pub struct VerifyArgs {
+    /// The path to the proof file.
+    #[clap(long)]
+    pub proof: PathBuf,
+
+    /// The chain ID the proof must have been generated for.
+    #[clap(long)]
+    pub chain_id: u64,
+
+    /// The path to the genesis json file the proof must have been generated with.
+    #[clap(long)]
+    pub genesis_path: Option<PathBuf>,
+
+    /// The custom beneficiary address the proof must have been generated with.
+    #[clap(long)]
+    pub custom_beneficiary: Option<Address>,
+}
+
+impl VerifyArgs {
+    /// Returns the genesis the proof must have been generated with.
+    pub fn genesis(&self) -> eyre::Result<Genesis> {
+        read_genesis(self.chain_id, self.genesis_path.as_deref())
+    }
+}
+
 impl HostArgs {
+    /// Returns the range of blocks to execute.
+    pub fn block_range(&self) -> eyre::Result<(u64, u64)> {
+        match (self.block_number, self.from_block, self.to_block) {
+            (Some(block_number), _, _) => Ok((block_number, block_number)),
+            (None, Some(from_block), Some(to_block)) => {
+                if from_block > to_block {
+                    eyre::bail!("--from-block must not be greater than --to-block")
+                }
+
+                Ok((from_block, to_block))
+            }
+            _ => eyre::bail!("either --block-number or --from-block and --to-block must be used"),
+        }
+    }
+
     pub async fn as_config(&self) -> eyre::Result<Config> {
         // We don't need RPC when using cache with known chain ID, so we leave it as `Option<Url>`
         // here and decide on whether to panic later.
@@ -70,7 +211,7 @@ impl HostArgs {
             }
             (Some(rpc_url), None) => {
                 // We can find out about chain ID from RPC.
-                let provider = RootProvider::<AnyNetwork>::new_http(rpc_url.clone());
+                let provider = create_provider::<AnyNetwork>(rpc_url.clone()).await?;
 
                 (Some(rpc_url), provider.get_chain_id().await?)
             }
@@ -85,14 +226,7 @@ impl HostArgs {
         });
         let debug_rpc_url = debug_rpc_url.or_else(|| rpc_url.clone());
 
-        let genesis = if let Some(genesis_path) = &self.genesis_path {
-            let genesis_json = fs::read_to_string(genesis_path)
-                .map_err(|err| eyre::eyre!("Failed to read genesis file: {err}"))?;
-
-            Genesis::Custom(genesis_json)
-        } else {
-            chain_id.try_into()?
-        };
+        let genesis = read_genesis(chain_id, self.genesis_path.as_deref())?;
 
         let chain = Chain::from_id(chain_id);
 
@@ -100,11 +234,24 @@ impl HostArgs {
             chain,
             genesis,
             rpc_url,
+            fallback_rpc_urls: self.provider.fallback_rpc_urls.clone(),
             debug_rpc_url,
+            retry_config: self.fetch.retry_config(),
             cache_dir: self.cache_dir.clone(),
+            cache_max_size: self.cache_max_size,
+            input_store_url: self.input_store_url.clone(),
+            compress_input_cache: self.compress_input_cache,
+            key_cache_dir: self.key_cache_dir.clone(),
+            fetch_config: self.fetch.fetch_config(),
+            rpc_cache_size: self.rpc_cache_size,
+            witness_source: self.witness_source,
+            diagnose_state_mismatch: self.diagnose_state_mismatch,
             custom_beneficiary: self.custom_beneficiary,
-            prove_mode: self.prove.then_some(ZKMProofKind::Compressed),
-            // prove_mode: self.prove.then_some(ZKMProofKind::Core),
+            prove_mode: self.prove.then_some(if self.aggregate {
+                ZKMProofKind::Compressed
+            } else {
+                self.proof_kind.into()
+            }),
             opcode_tracking: self.opcode_tracking,
         };
 
@@ -112,6 +259,19 @@ impl HostArgs {
     }
 }
 
+/// Returns the genesis of the given genesis file if any, and of the given chain otherwise.
+fn read_genesis(chain_id: u64, genesis_path: Option<&Path>) -> eyre::Result<Genesis> {
+    match genesis_path {
+        Some(genesis_path) => {
+            let genesis_json = fs::read_to_string(genesis_path)
+                .map_err(|err| eyre::eyre!("Failed to read genesis file: {err}"))?;
+
+            Ok(Genesis::Custom(genesis_json))
+        }
+        None => chain_id.try_into(),
+    }
+}
+
 /// The arguments for configuring the chain data provider.
 #[derive(Debug, Clone, Parser)]
 pub struct ProviderArgs {
@@ -123,7 +283,22 @@ pub struct ProviderArgs {
     /// DEBUG_RPC_{chain_id} env var. If DEBUG_RPC_{chain_id} is not set, will use the rpc_url.
     #[clap(long)]
     pub debug_rpc_url: Option<Url>,
+    /// The rpc urls failed over to, in order, when the rpc_url fails.
+    #[clap(long, value_delimiter = ',')]
+    pub fallback_rpc_urls: Vec<Url>,
+    /// Additional rpc urls whose responses to the proof, code and block requests are compared
+    /// with the ones of the rpc_url, the response of the majority being used.
+    #[clap(long, value_delimiter = ',', conflicts_with_all = ["record_rpc", "replay_rpc"])]
+    pub quorum_rpc_urls: Vec<Url>,
     /// The chain ID. If not provided, requires the rpc_url argument to be provided.
     #[clap(long)]
     pub chain_id: Option<u64>,
+    /// Optional path to a fixture file where the JSON-RPC requests and their responses are
+    /// recorded, to be replayed later with --replay-rpc.
+    #[clap(long, conflicts_with = "replay_rpc")]
+    pub record_rpc: Option<PathBuf>,
+    /// Optional path to a fixture file recorded with --record-rpc, serving the JSON-RPC responses
+    /// without any network access.
+    #[clap(long, requires = "chain_id")]
+    pub replay_rpc: Option<PathBuf>,
 }
@@ -1,18 +1,32 @@
 use std::sync::Arc;
 
+use alloy_provider::{Network, RootProvider};
 use clap::Parser;
+use eyre::OptionExt;
 use host_executor::{
-    bins::persist_report_hook::PersistExecutionReport, build_executor,
-    create_eth_block_execution_strategy_factory, BlockExecutor, EthExecutorComponents,
+    bins::{
+        persist_proof_hook::PersistProof, persist_report_hook::PersistExecutionReport,
+        solidity_fixture_hook::ExportSolidityFixture,
+    },
+    build_executor, create_eth_block_execution_strategy_factory,
+    create_op_block_execution_strategy_factory, decode_aggregation_public_values,
+    decode_public_values, verify_chain_config, verify_chain_config_hash, Aggregator, BlockExecutor,
+    Config, EthExecutorComponents, ExecutorComponents, NativeExecutor, OpExecutorComponents,
+    ProofArtifact, ProofProgram, RetryConfig,
 };
-use provider::create_provider;
+use provider::{
+    create_failover_provider, create_quorum_provider, create_recording_provider,
+    create_replay_provider, RpcRecorder,
+};
+use tracing::{error, info};
 use tracing_subscriber::{
     filter::EnvFilter, fmt, prelude::__tracing_subscriber_SubscriberExt, util::SubscriberInitExt,
 };
-use zkm_sdk::{include_elf, ProverClient};
+use url::Url;
+use zkm_sdk::{include_elf, HashableKey, Prover, ProverClient};
 
 mod cli;
-use cli::HostArgs;
+use cli::{HostArgs, HostCommand, VerifyArgs};
 
 #[tokio::main]
 async fn main() -> eyre::Result<()> {
@@ -37,7 +51,11 @@ async fn main() -> eyre::Result<()> {
 
     // Parse the command line arguments.
     let args = HostArgs::parse();
-    let block_number = args.block_number;
+
+    if let Some(HostCommand::Verify(verify_args)) = &args.command {
+        return verify(verify_args).await;
+    }
+
     let report_path = args.report_path.clone();
     let config = args.as_config().await?;
     let persist_execution_report = PersistExecutionReport::new(
@@ -46,27 +64,309 @@ async fn main() -> eyre::Result<()> {
         args.precompile_tracking,
         args.opcode_tracking,
     );
+    let hooks = (
+        persist_execution_report,
+        (
+            PersistProof::new(args.proof_dir.clone()),
+            args.fixture_dir.clone().map(ExportSolidityFixture::new),
+        ),
+    );
+
+    if config.genesis.is_optimism() {
+        let elf = include_elf!("reth-op").to_vec();
+        let block_execution_strategy_factory =
+            create_op_block_execution_strategy_factory(&config.genesis);
+
+        execute::<OpExecutorComponents<_>>(
+            elf,
+            block_execution_strategy_factory,
+            hooks,
+            config,
+            &args,
+        )
+        .await
+    } else {
+        let elf = include_elf!("reth").to_vec();
+        let block_execution_strategy_factory =
+            create_eth_block_execution_strategy_factory(&config.genesis, config.custom_beneficiary);
+
+        execute::<EthExecutorComponents<_>>(
+            elf,
+            block_execution_strategy_factory,
+            hooks,
+            config,
+            &args,
+        )
+        .await
+    }
+}
+
+async fn execute<C>(
+    elf: Vec<u8>,
+    evm_config: C::EvmConfig,
+    hooks: C::Hooks,
+    config: Config,
+    args: &HostArgs,
+) -> eyre::Result<()>
+where
+    C: ExecutorComponents<Prover = ProverClient>,
+{
+    let (from_block, to_block) = args.block_range()?;
+
+    let recorder = args.provider.record_rpc.as_ref().map(|_| RpcRecorder::new());
+    let (provider, debug_provider) = if let Some(replay_path) = &args.provider.replay_rpc {
+        let provider = create_replay_provider::<C::Network>(replay_path)?;
+        info!("Replaying the RPC responses recorded in {}", replay_path.display());
+
+        (Some(provider.clone()), Some(provider))
+    } else {
+        let provider = match &config.rpc_url {
+            Some(url) if !args.provider.quorum_rpc_urls.is_empty() => Some(
+                create_quorum_provider::<C::Network>(
+                    std::iter::once(url.clone())
+                        .chain(args.provider.quorum_rpc_urls.clone())
+                        .collect(),
+                    &config.retry_config,
+                )
+                .await?,
+            ),
+            Some(url) => Some(
+                create_rpc_provider::<C::Network>(
+                    std::iter::once(url.clone()).chain(config.fallback_rpc_urls.clone()).collect(),
+                    &config.retry_config,
+                    recorder.as_ref(),
+                )
+                .await?,
+            ),
+            None => None,
+        };
+        let debug_provider = match &config.debug_rpc_url {
+            Some(url) => Some(
+                create_rpc_provider::<C::Network>(
+                    vec![url.clone()],
+                    &config.retry_config,
+                    recorder.as_ref(),
+                )
+                .await?,
+            ),
+            None => None,
+        };
+
+        (provider, debug_provider)
+    };
+
+    if args.native {
+        let result = execute_native::<C>(provider, debug_provider, evm_config, config, args).await;
+        save_recording(recorder, args)?;
+
+        return result;
+    }
 
     let prover_client = Arc::new(ProverClient::new());
+    let key_cache_dir = config.key_cache_dir.clone();
+    let (genesis, custom_beneficiary) = (config.genesis.clone(), config.custom_beneficiary);
+    let result = async {
+        let executor = build_executor::<C, _>(
+            elf,
+            provider,
+            debug_provider,
+            evm_config,
+            prover_client.clone(),
+            hooks,
+            config,
+        )
+        .await?;
 
-    let elf = include_elf!("reth").to_vec();
-    let block_execution_strategy_factory =
-        create_eth_block_execution_strategy_factory(&config.genesis, config.custom_beneficiary);
-    let provider = config.rpc_url.as_ref().map(|url| create_provider(url.clone()));
-    let debug_provider = config.debug_rpc_url.as_ref().map(|url| create_provider(url.clone()));
-
-    let executor = build_executor::<EthExecutorComponents<_>, _>(
-        elf,
-        provider,
-        debug_provider,
-        block_execution_strategy_factory,
-        prover_client,
-        persist_execution_report,
-        config,
-    )
-    .await?;
+        if args.batch {
+            executor.execute_batch(from_block, to_block).await?;
+        } else if args.aggregate {
+            let mut proofs = Vec::new();
+            for block_number in from_block..=to_block {
+                let proof = executor.execute(block_number).await?;
+                proofs.push(proof.ok_or_eyre("Proving must be enabled to aggregate proofs")?);
+            }
+
+            let aggregator = Aggregator::try_new(
+                include_elf!("reth-aggregation").to_vec(),
+                prover_client.clone(),
+                key_cache_dir.as_deref(),
+            )
+            .await?;
+            // The aggregation proof is persisted like the block proofs.
+            let aggregation_hooks = (
+                PersistProof::new(args.proof_dir.clone()),
+                args.fixture_dir.clone().map(ExportSolidityFixture::new),
+            );
+            let proof = aggregator
+                .aggregate(
+                    &executor.vk(),
+                    proofs,
+                    args.proof_kind.into(),
+                    &genesis,
+                    custom_beneficiary,
+                    &aggregation_hooks,
+                )
+                .await?;
+            Prover::verify(prover_client.as_ref(), &proof, &aggregator.vk())?;
+        } else if let Some(block_hash) = args.block_hash {
+            executor.execute_block_hash(from_block, block_hash).await?;
+        } else {
+            for block_number in from_block..=to_block {
+                executor.execute(block_number).await?;
+            }
+        }
+
+        Ok::<_, eyre::Report>(())
+    }
+    .await;
+
+    save_recording(recorder, args)?;
+
+    result
+}
+
+/// Executes the blocks natively on the host, without setting up the zkVM.
+///
+/// When executing the blocks one by one, every block is executed even if some fail, so that a
+/// whole range of cached inputs can be checked at once. The failures are logged, and summed up
+/// in the returned error.
+async fn execute_native<C>(
+    provider: Option<RootProvider<C::Network>>,
+    debug_provider: Option<RootProvider<C::Network>>,
+    evm_config: C::EvmConfig,
+    config: Config,
+    args: &HostArgs,
+) -> eyre::Result<()>
+where
+    C: ExecutorComponents,
+{
+    let (from_block, to_block) = args.block_range()?;
+    let executor = NativeExecutor::<C, _>::try_new(provider, debug_provider, evm_config, config)?;
+
+    if args.batch {
+        executor.execute_pinned_batch(from_block, to_block, None).await?;
+    } else if let Some(block_hash) = args.block_hash {
+        executor.execute_pinned_batch(from_block, from_block, Some(block_hash)).await?;
+    } else {
+        let mut failed = Vec::new();
+        for block_number in from_block..=to_block {
+            if let Err(err) = executor.execute(block_number).await {
+                error!("Native execution of block {} failed: {:?}", block_number, err);
+                failed.push(block_number);
+            }
+        }
+
+        let total = to_block - from_block + 1;
+        info!("{} blocks passed, {} failed", total - failed.len() as u64, failed.len());
+        if !failed.is_empty() {
+            eyre::bail!(
+                "the native execution of {} out of {} blocks failed: {}",
+                failed.len(),
+                total,
+                failed.iter().map(u64::to_string).collect::<Vec<_>>().join(", ")
+            );
+        }
+    }
+
+    Ok(())
+}
+
+/// Saves the recorded RPC responses, if any.
+///
+/// The fixture is saved even if the execution failed, to be able to reproduce the failure.
+fn save_recording(recorder: Option<RpcRecorder>, args: &HostArgs) -> eyre::Result<()> {
+    if let (Some(recorder), Some(record_path)) = (recorder, &args.provider.record_rpc) {
+        recorder.save(record_path)?;
+        info!("{} RPC responses recorded in {}", recorder.len(), record_path.display());
+    }
+
+    Ok(())
+}
+
+/// Creates the provider of the RPCs, recording its responses if `recorder` is set.
+async fn create_rpc_provider<N: Network>(
+    rpc_urls: Vec<Url>,
+    retry_config: &RetryConfig,
+    recorder: Option<&RpcRecorder>,
+) -> eyre::Result<RootProvider<N>> {
+    let provider = match recorder {
+        Some(recorder) => create_recording_provider(rpc_urls, retry_config, recorder).await?,
+        None => create_failover_provider(rpc_urls, retry_config).await?,
+    };
+
+    Ok(provider)
+}
+
+/// Verifies a persisted proof against the verifying key of the guest program of the chain given on
+/// the command line, and checks that it was generated with the chain configuration of its genesis.
+async fn verify(args: &VerifyArgs) -> eyre::Result<()> {
+    let artifact = ProofArtifact::load(&args.proof)?;
+    let genesis = args.genesis()?;
+
+    let elf = if genesis.is_optimism() { include_elf!("reth-op") } else { include_elf!("reth") };
+
+    let prover_client = ProverClient::new();
+    let (_, block_vk) = prover_client.setup(elf);
+    let public_values = artifact.proof_with_public_values()?.public_values;
+
+    // The proven public values are checked against the unauthenticated fields of the artifact,
+    // and only they are reported.
+    let (chain_id, from_block, to_block, block_hash) = match artifact.program {
+        ProofProgram::Block => {
+            artifact.verify(&prover_client, &block_vk)?;
+
+            let public_values = decode_public_values(&public_values)?;
+            verify_chain_config(&public_values, &genesis, args.custom_beneficiary)?;
+
+            (
+                public_values.chain_id,
+                public_values.first_block_number,
+                public_values.block_number,
+                public_values.block_hash,
+            )
+        }
+        ProofProgram::Aggregation => {
+            let (_, vk) = prover_client.setup(include_elf!("reth-aggregation"));
+            artifact.verify(&prover_client, &vk)?;
+
+            // The aggregated proofs must be proofs of the guest program of the chain.
+            let public_values = decode_aggregation_public_values(&public_values)?;
+            if public_values.vkey_hash != block_vk.hash_u32() {
+                eyre::bail!("The aggregated proofs were not generated by the guest program");
+            }
+            verify_chain_config_hash(
+                public_values.chain_config_hash,
+                &genesis,
+                args.custom_beneficiary,
+            )?;
+
+            (
+                public_values.chain_id,
+                public_values.first_block_number,
+                public_values.block_number,
+                public_values.block_hash,
+            )
+        }
+    };
+
+    if (chain_id, from_block, to_block, block_hash) !=
+        (artifact.chain_id, artifact.from_block, artifact.to_block, artifact.block_hash)
+    {
+        eyre::bail!(
+            "The artifact claims chain {} blocks {}-{} ({}), but the proof is for chain {} blocks \
+             {}-{} ({})",
+            artifact.chain_id,
+            artifact.from_block,
+            artifact.to_block,
+            artifact.block_hash,
+            chain_id,
+            from_block,
+            to_block,
+            block_hash
+        );
+    }
 
-    executor.execute(block_number).await?;
+    info!(chain_id, from_block, to_block, block_hash = ?block_hash, "Proof successfully verified");
 
     Ok(())
 }
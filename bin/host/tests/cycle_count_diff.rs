@@ -31,8 +31,18 @@ async fn test_in_zkvm() {
         chain: Chain::mainnet(),
         genesis: Genesis::Mainnet,
         rpc_url: None,
+        fallback_rpc_urls: vec![],
         debug_rpc_url: None,
+        retry_config: Default::default(),
         cache_dir: None,
+        cache_max_size: None,
+        input_store_url: None,
+        compress_input_cache: false,
+        key_cache_dir: None,
+        fetch_config: Default::default(),
+        rpc_cache_size: 0,
+        witness_source: Default::default(),
+        diagnose_state_mismatch: false,
         custom_beneficiary: None,
         prove_mode: None,
         opcode_tracking: false,
@@ -21,6 +21,14 @@ pub enum ClientError {
     InvalidHeaderBlockNumber(u64, u64),
     #[error("Invalid parent header found for block \n expected: {}, found: {}", .0, .1)]
     InvalidHeaderParentHash(FixedBytes<32>, FixedBytes<32>),
+    #[error("Invalid public values: {}", .0)]
+    InvalidPublicValues(String),
+    #[error("No proofs to aggregate")]
+    EmptyAggregation,
+    #[error("Chain configuration mismatch in the proof of block {}", .0)]
+    InvalidAggregatedChainConfig(u64),
+    #[error("Invalid state root linkage \n expected: {}, found: {}", .0, .1)]
+    InvalidAggregatedStateRoot(FixedBytes<32>, FixedBytes<32>),
     #[error("Failed to validate post exectution state {}", 0)]
     PostExecutionError(#[from] ConsensusError),
     #[error("Block Execution Failed: {}", .0)]
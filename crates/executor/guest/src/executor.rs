@@ -1,10 +1,10 @@
-use std::sync::Arc;
+use std::{iter::once, sync::Arc};
 
 use alloy_consensus::{BlockHeader, Header};
-use alloy_primitives::B256;
+use alloy_primitives::{map::HashMap, B256};
 use itertools::Itertools;
 use primitives::is_goat_testnet;
-use reth_chainspec::ChainSpec;
+use reth_chainspec::{ChainSpec, EthChainSpec};
 use reth_errors::BlockExecutionError;
 use reth_evm::{
     execute::{BasicBlockExecutor, Executor},
@@ -51,35 +51,32 @@ impl<C, CS> ClientExecutor<C, CS>
 where
     C: ConfigureEvm,
     C::Primitives: FromInput + BlockValidator<CS>,
+    CS: EthChainSpec,
 {
+    /// Executes the current block and the subsequent blocks of the input in turn, verifying the
+    /// state root after each of them.
+    ///
+    /// Returns the header of the last executed block and the state root of the parent of the
+    /// current block.
     pub fn execute(
         &self,
         mut input: ClientExecutorInput<C::Primitives>,
     ) -> Result<(Header, B256), ClientError> {
-        let chain_id: u64 = (&input.genesis).try_into().expect("convert chain id err");
+        // Taken from the chain spec, as custom genesis files don't map to a known chain id.
+        let chain_id = self.chain_spec.chain_id();
 
         let sealed_headers = input.sealed_headers().collect::<Vec<_>>();
+        let subsequent_blocks = std::mem::take(&mut input.subsequent_blocks);
 
-        // Initialize the witnessed database with verified storage proofs.
-        let db = profile_report!(INIT_WITNESS_DB, {
-            let trie_db = input.witness_db(&sealed_headers).unwrap();
-            WrapDatabaseRef(trie_db)
+        // The witness database of the subsequent blocks is built on top of the updated parent
+        // state, reusing the block hashes verified for the current block.
+        let mut block_hashes = None;
+        let bytecodes_by_hash = (!subsequent_blocks.is_empty()).then(|| {
+            input.bytecodes.iter().map(|code| (code.hash_slow(), code)).collect::<HashMap<_, _>>()
         });
 
-        let block_executor: BlockExecutor<'_, C> =
-            BlockExecutor::new(self.evm_config.clone(), db, input.opcode_tracking, chain_id);
-
-        let block = profile_report!(RECOVER_SENDERS, {
-            C::Primitives::from_input_block(input.current_block.clone())
-                .try_into_recovered()
-                .map_err(|_| ClientError::SignatureRecoveryFailed)
-        })?;
-
-        // Validate the blocks.
+        // Validate the headers.
         profile_report!(VALIDATE_HEADER, {
-            C::Primitives::validate_block(&block, self.chain_spec.clone())
-                .expect("The block is invalid");
-
             for (header, parent) in sealed_headers.iter().tuple_windows() {
                 C::Primitives::validate_header(parent, self.chain_spec.clone())
                     .expect("A parent header is invalid");
@@ -93,66 +90,103 @@ where
             }
         });
 
-        let execution_output =
-            profile_report!(BLOCK_EXECUTION, { block_executor.execute(&block) })?;
-
-        // Validate the block post execution.
-        profile_report!(VALIDATE_EXECUTION, {
-            C::Primitives::validate_block_post_execution(
-                &block,
-                self.chain_spec.clone(),
-                &execution_output,
-                is_goat_testnet(chain_id),
-            )
-        })?;
-
-        // Convert the output to an execution outcome.
-        let executor_outcome = ExecutionOutcome::new(
-            execution_output.state,
-            vec![execution_output.result.receipts],
-            input.current_block.header().number(),
-            vec![execution_output.result.requests],
-        );
-
         let parent_state_root = input.parent_state.state_root();
+        let mut header = None;
+
+        for input_block in once(input.current_block.clone()).chain(subsequent_blocks) {
+            let db = match block_hashes.clone() {
+                Some(block_hashes) => WrapDatabaseRef(TrieDB::new(
+                    &input.parent_state,
+                    block_hashes,
+                    bytecodes_by_hash
+                        .clone()
+                        .expect("bytecodes are indexed when executing subsequent blocks"),
+                )),
+                None => {
+                    // Initialize the witnessed database with verified storage proofs.
+                    profile_report!(INIT_WITNESS_DB, {
+                        let trie_db = input.witness_db(&sealed_headers).unwrap();
+                        block_hashes = Some(trie_db.block_hashes().clone());
+                        WrapDatabaseRef(trie_db)
+                    })
+                }
+            };
+
+            let block_executor: BlockExecutor<'_, C> =
+                BlockExecutor::new(self.evm_config.clone(), db, input.opcode_tracking, chain_id);
+
+            let block = profile_report!(RECOVER_SENDERS, {
+                C::Primitives::from_input_block(input_block.clone())
+                    .try_into_recovered()
+                    .map_err(|_| ClientError::SignatureRecoveryFailed)
+            })?;
+
+            // Validate the block.
+            profile_report!(VALIDATE_HEADER, {
+                C::Primitives::validate_block(&block, self.chain_spec.clone())
+                    .expect("The block is invalid");
+            });
+
+            let execution_output =
+                profile_report!(BLOCK_EXECUTION, { block_executor.execute(&block) })?;
+
+            // Validate the block post execution.
+            profile_report!(VALIDATE_EXECUTION, {
+                C::Primitives::validate_block_post_execution(
+                    &block,
+                    self.chain_spec.clone(),
+                    &execution_output.result,
+                    is_goat_testnet(chain_id),
+                )
+            })?;
+
+            // Convert the output to an execution outcome.
+            let executor_outcome = ExecutionOutcome::new(
+                execution_output.state,
+                vec![execution_output.result.receipts],
+                input_block.header().number(),
+                vec![execution_output.result.requests],
+            );
+
+            // Verify the state root.
+            let state_root = profile_report!(COMPUTE_STATE_ROOT, {
+                input.parent_state.update(&executor_outcome.hash_state_slow::<KeccakKeyHasher>());
+                input.parent_state.state_root()
+            });
+
+            if state_root != input_block.header().state_root() {
+                return Err(ClientError::MismatchedStateRoot);
+            }
 
-        // Verify the state root.
-        let state_root = profile_report!(COMPUTE_STATE_ROOT, {
-            input.parent_state.update(&executor_outcome.hash_state_slow::<KeccakKeyHasher>());
-            input.parent_state.state_root()
-        });
-
-        if state_root != input.current_block.header().state_root() {
-            return Err(ClientError::MismatchedStateRoot);
+            // Derive the block header.
+            // Note: the receipts root and gas used are verified by
+            // `validate_block_post_execution`.
+            header = Some(Header {
+                parent_hash: input_block.header().parent_hash(),
+                ommers_hash: input_block.header().ommers_hash(),
+                beneficiary: input_block.header().beneficiary(),
+                state_root,
+                transactions_root: input_block.header().transactions_root(),
+                receipts_root: input_block.header().receipts_root(),
+                logs_bloom: input_block.logs_bloom,
+                difficulty: input_block.header().difficulty(),
+                number: input_block.header().number(),
+                gas_limit: input_block.header().gas_limit(),
+                gas_used: input_block.header().gas_used(),
+                timestamp: input_block.header().timestamp(),
+                extra_data: input_block.header().extra_data().clone(),
+                mix_hash: input_block.header().mix_hash().unwrap(),
+                nonce: input_block.header().nonce().unwrap(),
+                base_fee_per_gas: input_block.header().base_fee_per_gas(),
+                withdrawals_root: input_block.header().withdrawals_root(),
+                blob_gas_used: input_block.header().blob_gas_used(),
+                excess_blob_gas: input_block.header().excess_blob_gas(),
+                parent_beacon_block_root: input_block.header().parent_beacon_block_root(),
+                requests_hash: input_block.header().requests_hash(),
+            });
         }
 
-        // Derive the block header.
-        // Note: the receipts root and gas used are verified by `validate_block_post_execution`.
-        let header = Header {
-            parent_hash: input.current_block.header().parent_hash(),
-            ommers_hash: input.current_block.header().ommers_hash(),
-            beneficiary: input.current_block.header().beneficiary(),
-            state_root,
-            transactions_root: input.current_block.header().transactions_root(),
-            receipts_root: input.current_block.header().receipts_root(),
-            logs_bloom: input.current_block.logs_bloom,
-            difficulty: input.current_block.header().difficulty(),
-            number: input.current_block.header().number(),
-            gas_limit: input.current_block.header().gas_limit(),
-            gas_used: input.current_block.header().gas_used(),
-            timestamp: input.current_block.header().timestamp(),
-            extra_data: input.current_block.header().extra_data().clone(),
-            mix_hash: input.current_block.header().mix_hash().unwrap(),
-            nonce: input.current_block.header().nonce().unwrap(),
-            base_fee_per_gas: input.current_block.header().base_fee_per_gas(),
-            withdrawals_root: input.current_block.header().withdrawals_root(),
-            blob_gas_used: input.current_block.header().blob_gas_used(),
-            excess_blob_gas: input.current_block.header().excess_blob_gas(),
-            parent_beacon_block_root: input.current_block.header().parent_beacon_block_root(),
-            requests_hash: input.current_block.header().requests_hash(),
-        };
-
-        Ok((header, parent_state_root))
+        Ok((header.expect("at least one block is executed"), parent_state_root))
     }
 }
 
@@ -271,3 +305,182 @@ where
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use alloy_consensus::{
+        constants::EMPTY_OMMER_ROOT_HASH, proofs::calculate_transaction_root,
+        transaction::SignerRecoverable, BlockBody, SignableTransaction, TxLegacy, EMPTY_ROOT_HASH,
+    };
+    use alloy_primitives::{keccak256, logs_bloom, Bytes, TxKind, U256};
+    use mpt::EthereumState;
+    use primitives::genesis::Genesis;
+    use reth_chainspec::{BaseFeeParams, Chain, ChainSpecBuilder};
+    use reth_ethereum_primitives::{EthPrimitives, Receipt, TransactionSigned};
+    use reth_execution_types::BlockExecutionResult;
+    use reth_primitives_traits::{crypto::secp256k1::sign_message, Account};
+    use reth_trie::HashedPostState;
+    use revm::{
+        database::{BundleState, CacheDB, EmptyDB},
+        state::{AccountInfo, Bytecode},
+    };
+
+    use super::*;
+
+    const CHAIN_ID: u64 = 1337;
+
+    /// The runtime code of the deployed contract, storing 1 at slot 0.
+    const RUNTIME_CODE: [u8; 6] = [0x60, 0x01, 0x60, 0x00, 0x55, 0x00];
+
+    /// The init code of the deployed contract, returning the [`RUNTIME_CODE`] appended to it.
+    const INIT_CODE: [u8; 12] =
+        [0x60, 0x06, 0x60, 0x0c, 0x60, 0x00, 0x39, 0x60, 0x06, 0x60, 0x00, 0xf3];
+
+    fn sign(secret: B256, tx: TxLegacy) -> TransactionSigned {
+        let signature = sign_message(secret, tx.signature_hash()).unwrap();
+        tx.into_signed(signature).into()
+    }
+
+    /// Executes the blocks in turn on top of the given accounts, returning the result of the last
+    /// block and the state changes of all of them.
+    fn execute_blocks(
+        client_executor: &EthClientExecutor,
+        accounts: &[(Address, AccountInfo)],
+        blocks: &[reth_ethereum_primitives::Block],
+    ) -> (BlockExecutionResult<Receipt>, BundleState) {
+        let mut db = CacheDB::new(EmptyDB::default());
+        for (address, info) in accounts {
+            db.insert_account_info(*address, info.clone());
+        }
+
+        let mut executor =
+            BasicBlockExecutor::new(client_executor.evm_config.clone(), db, Some(CHAIN_ID));
+        let mut result = None;
+        for block in blocks {
+            let block = block.clone().try_into_recovered().unwrap();
+            result = Some(executor.execute_one(&block).unwrap());
+        }
+
+        (result.unwrap(), executor.into_state().take_bundle())
+    }
+
+    /// Builds the child of the last block with the given transactions, executing the chain to
+    /// fill in its header.
+    fn next_block(
+        client_executor: &EthClientExecutor,
+        accounts: &[(Address, AccountInfo)],
+        parent_state: &EthereumState,
+        parent: &Header,
+        blocks: &[reth_ethereum_primitives::Block],
+        transactions: Vec<TransactionSigned>,
+    ) -> reth_ethereum_primitives::Block {
+        let header = Header {
+            parent_hash: parent.hash_slow(),
+            ommers_hash: EMPTY_OMMER_ROOT_HASH,
+            beneficiary: Address::repeat_byte(0xbe),
+            transactions_root: calculate_transaction_root(&transactions),
+            difficulty: U256::from(1),
+            number: parent.number + 1,
+            gas_limit: parent.gas_limit,
+            timestamp: parent.timestamp + 12,
+            base_fee_per_gas: parent.next_block_base_fee(BaseFeeParams::ethereum()),
+            ..Default::default()
+        };
+        let mut block = reth_ethereum_primitives::Block {
+            header,
+            body: BlockBody { transactions, ommers: vec![], withdrawals: None },
+        };
+
+        let chain = blocks.iter().cloned().chain(once(block.clone())).collect::<Vec<_>>();
+        let (result, bundle_state) = execute_blocks(client_executor, accounts, &chain);
+
+        let mut state = parent_state.clone();
+        state.update(&HashedPostState::from_bundle_state::<KeccakKeyHasher>(&bundle_state.state));
+
+        block.header.state_root = state.state_root();
+        block.header.receipts_root = Receipt::calculate_receipt_root_no_memo(&result.receipts);
+        block.header.logs_bloom = logs_bloom(result.receipts.iter().flat_map(|r| &r.logs));
+        block.header.gas_used = result.gas_used;
+        block
+    }
+
+    #[test]
+    fn test_execute_subsequent_blocks() {
+        let chain_spec = Arc::new(
+            ChainSpecBuilder::default()
+                .chain(Chain::from_id(CHAIN_ID))
+                .genesis(Default::default())
+                .london_activated()
+                .build(),
+        );
+        let client_executor = EthClientExecutor::eth(chain_spec, None);
+
+        // The first block deploys a contract, the second one calls it.
+        let secret = B256::repeat_byte(0x42);
+        let tx = TxLegacy {
+            chain_id: Some(CHAIN_ID),
+            gas_price: 1_000_000_000,
+            gas_limit: 200_000,
+            ..Default::default()
+        };
+        let deploy = sign(
+            secret,
+            TxLegacy {
+                to: TxKind::Create,
+                input: Bytes::from([INIT_CODE.as_slice(), RUNTIME_CODE.as_slice()].concat()),
+                ..tx.clone()
+            },
+        );
+        let sender = deploy.recover_signer().unwrap();
+        let call = sign(secret, TxLegacy { nonce: 1, to: TxKind::Call(sender.create(0)), ..tx });
+
+        let balance = U256::from(10).pow(U256::from(18));
+        let accounts = [(sender, AccountInfo { balance, ..Default::default() })];
+        let mut parent_state =
+            EthereumState::from_proofs(EMPTY_ROOT_HASH, &Default::default()).unwrap();
+        let mut post_state = HashedPostState::default();
+        post_state
+            .accounts
+            .insert(keccak256(sender), Some(Account { nonce: 0, balance, bytecode_hash: None }));
+        parent_state.update(&post_state);
+
+        let parent = Header {
+            ommers_hash: EMPTY_OMMER_ROOT_HASH,
+            state_root: parent_state.state_root(),
+            difficulty: U256::from(1),
+            gas_limit: 30_000_000,
+            base_fee_per_gas: Some(1_000_000_000),
+            ..Default::default()
+        };
+        let current_block =
+            next_block(&client_executor, &accounts, &parent_state, &parent, &[], vec![deploy]);
+        let last_block = next_block(
+            &client_executor,
+            &accounts,
+            &parent_state,
+            &current_block.header,
+            &[current_block.clone()],
+            vec![call],
+        );
+
+        let input = ClientExecutorInput::<EthPrimitives> {
+            current_block,
+            subsequent_blocks: vec![last_block.clone()],
+            ancestor_headers: vec![parent.clone()],
+            parent_state,
+            bytecodes: vec![Bytecode::new_raw(Bytes::from_static(&RUNTIME_CODE))],
+            genesis: Genesis::Mainnet,
+            custom_beneficiary: None,
+            opcode_tracking: false,
+        };
+
+        // The code of the contract created by the current block is required by the next one.
+        let mut missing_code = input.clone();
+        missing_code.bytecodes.clear();
+        assert!(client_executor.execute(missing_code).is_err());
+
+        let (header, parent_state_root) = client_executor.execute(input).unwrap();
+        assert_eq!(header.hash_slow(), last_block.header.hash_slow());
+        assert_eq!(parent_state_root, parent.state_root);
+    }
+}
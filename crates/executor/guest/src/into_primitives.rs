@@ -8,7 +8,7 @@ use reth_consensus_common::validation::validate_body_against_header;
 use reth_errors::ConsensusError;
 use reth_ethereum_consensus::EthBeaconConsensus;
 use reth_ethereum_primitives::EthPrimitives;
-use reth_execution_types::BlockExecutionOutput;
+use reth_execution_types::BlockExecutionResult;
 use reth_primitives_traits::{NodePrimitives, RecoveredBlock, SealedHeader};
 
 pub trait IntoPrimitives<N: Network>: NodePrimitives {
@@ -42,7 +42,7 @@ pub trait BlockValidator<CS>: NodePrimitives {
     fn validate_block_post_execution(
         block: &RecoveredBlock<Self::Block>,
         chain_spec: Arc<CS>,
-        execution_output: &BlockExecutionOutput<Self::Receipt>,
+        execution_result: &BlockExecutionResult<Self::Receipt>,
         is_goat_testnet: bool,
     ) -> Result<(), ConsensusError>;
 }
@@ -104,14 +104,14 @@ impl BlockValidator<ChainSpec> for EthPrimitives {
     fn validate_block_post_execution(
         block: &RecoveredBlock<Self::Block>,
         chain_spec: Arc<ChainSpec>,
-        execution_output: &BlockExecutionOutput<Self::Receipt>,
+        execution_result: &BlockExecutionResult<Self::Receipt>,
         is_goat_testnet: bool,
     ) -> Result<(), ConsensusError> {
         reth_ethereum_consensus::validate_block_post_execution(
             block,
             &chain_spec,
-            &execution_output.result.receipts,
-            &execution_output.result.requests,
+            &execution_result.receipts,
+            &execution_result.requests,
             is_goat_testnet,
         )
     }
@@ -186,13 +186,13 @@ impl BlockValidator<reth_optimism_chainspec::OpChainSpec>
     fn validate_block_post_execution(
         block: &RecoveredBlock<Self::Block>,
         chain_spec: Arc<reth_optimism_chainspec::OpChainSpec>,
-        execution_output: &BlockExecutionOutput<Self::Receipt>,
+        execution_result: &BlockExecutionResult<Self::Receipt>,
         _is_goat_testnet: bool,
     ) -> Result<(), ConsensusError> {
         reth_optimism_consensus::validate_block_post_execution(
             block.header(),
             &chain_spec,
-            &execution_output.result,
+            execution_result,
         )
     }
 }
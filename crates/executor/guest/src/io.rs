@@ -37,11 +37,18 @@ pub struct ClientExecutorInput<P: NodePrimitives> {
         as = "reth_primitives_traits::serde_bincode_compat::Block<'_, P::SignedTx, Header>"
     )]
     pub current_block: Block<P::SignedTx>,
+    /// The blocks following the current block, in ascending order. They are executed in turn on
+    /// top of the post-state of the current block, so that a whole range of consecutive blocks
+    /// can be verified in a single run.
+    #[serde_as(
+        as = "Vec<reth_primitives_traits::serde_bincode_compat::Block<'_, P::SignedTx, Header>>"
+    )]
+    pub subsequent_blocks: Vec<Block<P::SignedTx>>,
     /// The previous block headers starting from the most recent. There must be at least one header
     /// to provide the parent state root.
     #[serde_as(as = "Vec<alloy_consensus::serde_bincode_compat::Header>")]
     pub ancestor_headers: Vec<Header>,
-    /// Network state as of the parent block.
+    /// Network state as of the parent block of the current block.
     pub parent_state: EthereumState,
     /// Account bytecodes.
     pub bytecodes: Vec<Bytecode>,
@@ -60,6 +67,12 @@ impl<P: NodePrimitives> ClientExecutorInput<P> {
         &self.ancestor_headers[0]
     }
 
+    /// Gets the last block to be executed.
+    #[inline(always)]
+    pub fn last_block(&self) -> &Block<P::SignedTx> {
+        self.subsequent_blocks.last().unwrap_or(&self.current_block)
+    }
+
     /// Creates a [`WitnessDb`].
     pub fn witness_db(&self, sealed_headers: &[SealedHeader]) -> Result<TrieDB<'_>, ClientError> {
         <Self as WitnessInput>::witness_db(self, sealed_headers)
@@ -84,11 +97,196 @@ impl<P: NodePrimitives> WitnessInput for ClientExecutorInput<P> {
 
     #[inline(always)]
     fn sealed_headers(&self) -> impl Iterator<Item = SealedHeader> {
-        once(SealedHeader::seal_slow(self.current_block.header.clone()))
+        self.subsequent_blocks
+            .iter()
+            .rev()
+            .map(|b| SealedHeader::seal_slow(b.header.clone()))
+            .chain(once(SealedHeader::seal_slow(self.current_block.header.clone())))
             .chain(self.ancestor_headers.iter().map(|h| SealedHeader::seal_slow(h.clone())))
     }
 }
 
+/// The version of the [BlockPublicValues] layout. Bump it whenever the layout changes.
+pub const PUBLIC_VALUES_VERSION: u8 = 3;
+
+/// The public values committed by the guest programs at the end of execution.
+///
+/// When a range of blocks is executed, `parent_hash` and `prev_state_root` refer to the parent of
+/// the first block, and the remaining fields refer to the last block.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub struct BlockPublicValues {
+    /// The layout version, always serialized first.
+    pub version: u8,
+    /// The chain id.
+    pub chain_id: u64,
+    /// The hash of the chain configuration used for execution, see
+    /// [primitives::chain_spec::chain_config_hash].
+    pub chain_config_hash: B256,
+    /// The number of the first executed block.
+    pub first_block_number: u64,
+    /// The parent hash of the first executed block.
+    pub parent_hash: B256,
+    /// The hash of the last executed block.
+    pub block_hash: B256,
+    /// The number of the last executed block.
+    pub block_number: u64,
+    /// The state root before execution.
+    pub prev_state_root: B256,
+    /// The state root after execution.
+    pub state_root: B256,
+    /// The receipts root of the last executed block.
+    pub receipts_root: B256,
+    /// The timestamp of the last executed block.
+    pub timestamp: u64,
+}
+
+impl BlockPublicValues {
+    /// Creates the public values from the header of the last executed block.
+    pub fn new(
+        chain_id: u64,
+        chain_config_hash: B256,
+        first_block_number: u64,
+        parent_hash: B256,
+        header: &Header,
+        prev_state_root: B256,
+    ) -> Self {
+        Self {
+            version: PUBLIC_VALUES_VERSION,
+            chain_id,
+            chain_config_hash,
+            first_block_number,
+            parent_hash,
+            block_hash: header.hash_slow(),
+            block_number: header.number,
+            prev_state_root,
+            state_root: header.state_root,
+            receipts_root: header.receipts_root,
+            timestamp: header.timestamp,
+        }
+    }
+
+    /// Decodes the public values, checking the layout version first.
+    pub fn decode(bytes: &[u8]) -> Result<Self, ClientError> {
+        match bytes.first() {
+            Some(&PUBLIC_VALUES_VERSION) => bincode::deserialize(bytes)
+                .map_err(|err| ClientError::InvalidPublicValues(err.to_string())),
+            Some(version) => Err(ClientError::InvalidPublicValues(format!(
+                "unsupported version {version}, expected {PUBLIC_VALUES_VERSION}"
+            ))),
+            None => Err(ClientError::InvalidPublicValues("empty public values".to_string())),
+        }
+    }
+}
+
+/// The version of the [AggregationPublicValues] layout. Bump it whenever the layout changes.
+pub const AGGREGATION_PUBLIC_VALUES_VERSION: u8 = 2;
+
+/// The input for the aggregation program.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct AggregationInput {
+    /// The hash of the verifying key of the block proofs.
+    pub vkey_hash: [u32; 8],
+    /// The public values of the block proofs, in ascending block order.
+    pub public_values: Vec<Vec<u8>>,
+}
+
+/// The public values committed by the aggregation program.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub struct AggregationPublicValues {
+    /// The layout version, always serialized first.
+    pub version: u8,
+    /// The hash of the verifying key of the aggregated block proofs.
+    pub vkey_hash: [u32; 8],
+    /// The chain id.
+    pub chain_id: u64,
+    /// The hash of the chain configuration shared by all the aggregated proofs.
+    pub chain_config_hash: B256,
+    /// The number of the first block.
+    pub first_block_number: u64,
+    /// The parent hash of the first block.
+    pub parent_hash: B256,
+    /// The hash of the last block.
+    pub block_hash: B256,
+    /// The number of the last block.
+    pub block_number: u64,
+    /// The state root before the first block.
+    pub prev_state_root: B256,
+    /// The state root after the last block.
+    pub state_root: B256,
+    /// The number of aggregated proofs.
+    pub proof_count: u64,
+}
+
+impl AggregationPublicValues {
+    /// Aggregates the public values of consecutive block proofs, checking that they are linked by
+    /// their parent hashes and state roots, and share the same chain configuration.
+    pub fn aggregate(
+        vkey_hash: [u32; 8],
+        blocks: &[BlockPublicValues],
+    ) -> Result<Self, ClientError> {
+        let (first, last) = match (blocks.first(), blocks.last()) {
+            (Some(first), Some(last)) => (first, last),
+            _ => return Err(ClientError::EmptyAggregation),
+        };
+
+        for (previous, current) in blocks.iter().tuple_windows() {
+            if current.chain_id != first.chain_id ||
+                current.chain_config_hash != first.chain_config_hash
+            {
+                return Err(ClientError::InvalidAggregatedChainConfig(current.block_number));
+            }
+
+            if current.parent_hash != previous.block_hash {
+                return Err(ClientError::InvalidHeaderParentHash(
+                    previous.block_hash,
+                    current.parent_hash,
+                ));
+            }
+
+            if current.prev_state_root != previous.state_root {
+                return Err(ClientError::InvalidAggregatedStateRoot(
+                    previous.state_root,
+                    current.prev_state_root,
+                ));
+            }
+
+            if current.first_block_number != previous.block_number + 1 {
+                return Err(ClientError::InvalidHeaderBlockNumber(
+                    previous.block_number + 1,
+                    current.first_block_number,
+                ));
+            }
+        }
+
+        Ok(Self {
+            version: AGGREGATION_PUBLIC_VALUES_VERSION,
+            vkey_hash,
+            chain_id: first.chain_id,
+            chain_config_hash: first.chain_config_hash,
+            first_block_number: first.first_block_number,
+            parent_hash: first.parent_hash,
+            block_hash: last.block_hash,
+            block_number: last.block_number,
+            prev_state_root: first.prev_state_root,
+            state_root: last.state_root,
+            proof_count: blocks.len() as u64,
+        })
+    }
+
+    /// Decodes the public values, checking the layout version first.
+    pub fn decode(bytes: &[u8]) -> Result<Self, ClientError> {
+        match bytes.first() {
+            Some(&AGGREGATION_PUBLIC_VALUES_VERSION) => bincode::deserialize(bytes)
+                .map_err(|err| ClientError::InvalidPublicValues(err.to_string())),
+            Some(version) => Err(ClientError::InvalidPublicValues(format!(
+                "unsupported aggregation version {version}, expected \
+                 {AGGREGATION_PUBLIC_VALUES_VERSION}"
+            ))),
+            None => Err(ClientError::InvalidPublicValues("empty public values".to_string())),
+        }
+    }
+}
+
 // The headed committed at the end of execution
 #[serde_as]
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
@@ -118,6 +316,11 @@ impl<'a> TrieDB<'a> {
     ) -> Self {
         Self { inner, block_hashes, bytecode_by_hash }
     }
+
+    /// Gets the verified block hashes, indexed by block number.
+    pub fn block_hashes(&self) -> &HashMap<u64, B256> {
+        &self.block_hashes
+    }
 }
 
 impl DatabaseRef for TrieDB<'_> {
@@ -143,7 +346,10 @@ impl DatabaseRef for TrieDB<'_> {
 
     /// Get account code by its hash.
     fn code_by_hash_ref(&self, hash: B256) -> Result<Bytecode, Self::Error> {
-        Ok(self.bytecode_by_hash.get(&hash).map(|code| (*code).clone()).unwrap())
+        self.bytecode_by_hash
+            .get(&hash)
+            .map(|code| (*code).clone())
+            .ok_or_else(|| ProviderError::TrieWitnessError(format!("Code not found for {hash}")))
     }
 
     /// Get storage value of address at index.
@@ -185,7 +391,7 @@ pub trait WitnessInput {
     fn bytecodes(&self) -> impl Iterator<Item = &Bytecode>;
 
     /// Gets an iterator over references to a consecutive, reverse-chronological block headers
-    /// starting from the current block header.
+    /// starting from the header of the last block to execute.
     fn sealed_headers(&self) -> impl Iterator<Item = SealedHeader>;
 
     /// Creates a [`WitnessDb`] from a [`WitnessInput`] implementation. To do so, it verifies the
@@ -239,3 +445,100 @@ pub trait WitnessInput {
         Ok(TrieDB::new(state, block_hashes, bytecodes_by_hash))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Returns the public values of the block `number`, linked to the block `number - 1`.
+    fn block(number: u64) -> BlockPublicValues {
+        BlockPublicValues {
+            version: PUBLIC_VALUES_VERSION,
+            chain_id: 1,
+            chain_config_hash: B256::repeat_byte(0xcc),
+            first_block_number: number,
+            parent_hash: B256::with_last_byte(number as u8 - 1),
+            block_hash: B256::with_last_byte(number as u8),
+            block_number: number,
+            prev_state_root: B256::left_padding_from(&[1, number as u8 - 1]),
+            state_root: B256::left_padding_from(&[1, number as u8]),
+            receipts_root: B256::ZERO,
+            timestamp: number,
+        }
+    }
+
+    #[test]
+    fn test_aggregate() {
+        let public_values =
+            AggregationPublicValues::aggregate([1; 8], &[block(1), block(2), block(3)]).unwrap();
+
+        assert_eq!(public_values.version, AGGREGATION_PUBLIC_VALUES_VERSION);
+        assert_eq!(public_values.first_block_number, 1);
+        assert_eq!(public_values.parent_hash, block(1).parent_hash);
+        assert_eq!(public_values.prev_state_root, block(1).prev_state_root);
+        assert_eq!(public_values.block_hash, block(3).block_hash);
+        assert_eq!(public_values.state_root, block(3).state_root);
+        assert_eq!(public_values.block_number, 3);
+        assert_eq!(public_values.proof_count, 3);
+
+        let encoded = bincode::serialize(&public_values).unwrap();
+        assert_eq!(AggregationPublicValues::decode(&encoded).unwrap(), public_values);
+    }
+
+    #[test]
+    fn test_aggregate_rejects_gaps() {
+        assert!(matches!(
+            AggregationPublicValues::aggregate([1; 8], &[block(1), block(3)]),
+            Err(ClientError::InvalidHeaderParentHash(..))
+        ));
+
+        // A block linked by its parent hash but not by its state root.
+        let mut next = block(2);
+        next.prev_state_root = B256::repeat_byte(0xff);
+        assert!(matches!(
+            AggregationPublicValues::aggregate([1; 8], &[block(1), next]),
+            Err(ClientError::InvalidAggregatedStateRoot(..))
+        ));
+
+        // A block linked by its parent hash but not by its number.
+        let mut next = block(2);
+        next.first_block_number = 3;
+        assert!(matches!(
+            AggregationPublicValues::aggregate([1; 8], &[block(1), next]),
+            Err(ClientError::InvalidHeaderBlockNumber(2, 3))
+        ));
+    }
+
+    #[test]
+    fn test_aggregate_rejects_wrong_order() {
+        assert!(matches!(
+            AggregationPublicValues::aggregate([1; 8], &[block(2), block(1)]),
+            Err(ClientError::InvalidHeaderParentHash(..))
+        ));
+    }
+
+    #[test]
+    fn test_aggregate_rejects_chain_mismatch() {
+        let mut next = block(2);
+        next.chain_id = 10;
+        assert!(matches!(
+            AggregationPublicValues::aggregate([1; 8], &[block(1), next]),
+            Err(ClientError::InvalidAggregatedChainConfig(2))
+        ));
+
+        let mut next = block(2);
+        next.chain_config_hash = B256::repeat_byte(0xdd);
+        assert!(matches!(
+            AggregationPublicValues::aggregate([1; 8], &[block(1), next]),
+            Err(ClientError::InvalidAggregatedChainConfig(2))
+        ));
+    }
+
+    #[test]
+    fn test_aggregate_rejects_empty() {
+        assert!(matches!(
+            AggregationPublicValues::aggregate([1; 8], &[]),
+            Err(ClientError::EmptyAggregation)
+        ));
+    }
+}
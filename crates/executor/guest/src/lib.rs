@@ -12,22 +12,57 @@ pub mod tracking;
 mod into_primitives;
 pub use into_primitives::{BlockValidator, FromInput, IntoInput, IntoPrimitives};
 
-use alloy_primitives::B256;
 use executor::{EthClientExecutor, DESERIALZE_INPUTS};
-use io::EthClientExecutorInput;
+use io::{BlockPublicValues, EthClientExecutorInput};
+use primitives::chain_spec::chain_config_hash;
+use reth_chainspec::{ChainSpec, EthChainSpec};
 use std::sync::Arc;
 
-pub fn verify_block(input: &[u8]) -> (B256, B256, B256) {
+/// Verifies the block(s) of the serialized input, returning the public values to commit.
+pub fn verify_block(input: &[u8]) -> BlockPublicValues {
     println!("cycle-tracker-report-start: {DESERIALZE_INPUTS}");
     let input = bincode::deserialize::<EthClientExecutorInput>(input).unwrap();
     println!("cycle-tracker-report-end: {DESERIALZE_INPUTS}");
 
     // Execute the block.
-    let executor = EthClientExecutor::eth(
-        Arc::new((&input.genesis).try_into().unwrap()),
-        input.custom_beneficiary,
-    );
+    let chain_spec: Arc<ChainSpec> = Arc::new((&input.genesis).try_into().unwrap());
+    let chain_id = chain_spec.chain_id();
+    let chain_config_hash = chain_config_hash(&chain_spec, input.custom_beneficiary);
+    let executor = EthClientExecutor::eth(chain_spec, input.custom_beneficiary);
+    let (first_block_number, parent_hash) =
+        (input.current_block.header.number, input.current_block.header.parent_hash);
     let (header, prev_state_root) = executor.execute(input).expect("failed to execute client");
-    let block_hash = header.hash_slow();
-    (block_hash, header.state_root, prev_state_root)
+    BlockPublicValues::new(
+        chain_id,
+        chain_config_hash,
+        first_block_number,
+        parent_hash,
+        &header,
+        prev_state_root,
+    )
+}
+
+#[cfg(feature = "optimism")]
+pub fn verify_op_block(input: &[u8]) -> BlockPublicValues {
+    println!("cycle-tracker-report-start: {DESERIALZE_INPUTS}");
+    let input = bincode::deserialize::<io::OpClientExecutorInput>(input).unwrap();
+    println!("cycle-tracker-report-end: {DESERIALZE_INPUTS}");
+
+    // Execute the block.
+    let chain_spec: Arc<reth_optimism_chainspec::OpChainSpec> =
+        Arc::new((&input.genesis).try_into().unwrap());
+    let chain_id = chain_spec.chain_id();
+    let chain_config_hash = chain_config_hash(&chain_spec.inner, input.custom_beneficiary);
+    let executor = executor::OpClientExecutor::optimism(chain_spec);
+    let (first_block_number, parent_hash) =
+        (input.current_block.header.number, input.current_block.header.parent_hash);
+    let (header, prev_state_root) = executor.execute(input).expect("failed to execute client");
+    BlockPublicValues::new(
+        chain_id,
+        chain_config_hash,
+        first_block_number,
+        parent_hash,
+        &header,
+        prev_state_root,
+    )
 }
@@ -0,0 +1,148 @@
+use std::{
+    fmt::{Debug, Formatter},
+    path::Path,
+    sync::Arc,
+    time::Instant,
+};
+
+use eyre::{bail, eyre};
+use guest_executor::io::{AggregationInput, AggregationPublicValues};
+use primitives::genesis::Genesis;
+use revm_primitives::Address;
+use tokio::task;
+use tracing::info;
+use zkm_prover::components::DefaultProverComponents;
+use zkm_sdk::{
+    HashableKey, Prover, ZKMProof, ZKMProofKind, ZKMProofWithPublicValues, ZKMProvingKey, ZKMStdin,
+    ZKMVerifyingKey,
+};
+
+use crate::{
+    decode_public_values,
+    keys::{elf_id, setup_keys},
+    verify_chain_config, ExecutionHooks, ProofProgram,
+};
+
+/// Aggregates the compressed proofs of consecutive blocks into a single proof, by verifying them
+/// recursively inside the aggregation program.
+pub struct Aggregator<P> {
+    client: Arc<P>,
+    pk: Arc<ZKMProvingKey>,
+    vk: Arc<ZKMVerifyingKey>,
+    elf_id: String,
+}
+
+impl<P> Aggregator<P>
+where
+    P: Prover<DefaultProverComponents> + 'static,
+{
+    /// Creates a new [Aggregator] from the aggregation program ELF, loading the keys from the
+    /// key cache directory if provided.
+    pub async fn try_new(
+        elf: Vec<u8>,
+        client: Arc<P>,
+        key_cache_dir: Option<&Path>,
+    ) -> eyre::Result<Self> {
+        let (pk, vk) = setup_keys(client.clone(), elf, key_cache_dir).await?;
+
+        let elf_id = elf_id(&pk.elf);
+
+        Ok(Self { client, pk: Arc::new(pk), vk: Arc::new(vk), elf_id })
+    }
+
+    /// Returns the verifying key of the aggregation program.
+    pub fn vk(&self) -> Arc<ZKMVerifyingKey> {
+        self.vk.clone()
+    }
+
+    /// Aggregates the compressed proofs of consecutive blocks, generated with `block_vk`, into a
+    /// single proof of the given kind.
+    ///
+    /// The proofs must be sorted in ascending block order, each block being the parent of the
+    /// first block of the next proof, and generated with the chain configuration of `genesis`.
+    /// The proving hooks are notified of the aggregation proof, with the last aggregated block.
+    pub async fn aggregate<H: ExecutionHooks>(
+        &self,
+        block_vk: &ZKMVerifyingKey,
+        proofs: Vec<ZKMProofWithPublicValues>,
+        proof_kind: ZKMProofKind,
+        genesis: &Genesis,
+        custom_beneficiary: Option<Address>,
+        hooks: &H,
+    ) -> eyre::Result<ZKMProofWithPublicValues> {
+        let vkey_hash = block_vk.hash_u32();
+
+        // Check the linkage on the host to fail early, it's checked again by the program.
+        let blocks = proofs
+            .iter()
+            .map(|proof| decode_public_values(&proof.public_values))
+            .collect::<Result<Vec<_>, _>>()?;
+        for block in &blocks {
+            verify_chain_config(block, genesis, custom_beneficiary)?;
+        }
+        let public_values = AggregationPublicValues::aggregate(vkey_hash, &blocks)
+            .map_err(|err| eyre!("Failed to aggregate the block proofs: {err}"))?;
+
+        let input = AggregationInput {
+            vkey_hash,
+            public_values: proofs
+                .iter()
+                .map(|proof| proof.public_values.as_slice().to_vec())
+                .collect(),
+        };
+
+        let mut stdin = ZKMStdin::new();
+        stdin.write_vec(bincode::serialize(&input)?);
+
+        for proof in proofs {
+            let ZKMProof::Compressed(proof) = proof.proof else {
+                bail!("Only compressed proofs can be aggregated");
+            };
+            stdin.write_proof(*proof, block_vk.vk.clone());
+        }
+
+        info!(
+            "Aggregating {} proofs up to block {}",
+            public_values.proof_count, public_values.block_number
+        );
+
+        let proving_start = Instant::now();
+        hooks.on_proving_start(public_values.block_number).await?;
+        let client = self.client.clone();
+        let pk = self.pk.clone();
+        let elf_id = Some(self.elf_id.clone());
+
+        let (proof, cycles) = task::spawn_blocking(move || {
+            client.prove_with_cycles(&pk, &stdin, proof_kind, elf_id).map_err(|err| eyre!("{err}"))
+        })
+        .await
+        .map_err(|err| eyre!("{err}"))??;
+
+        let proving_duration = proving_start.elapsed();
+        info!(
+            "Aggregation proof successfully generated! cycles: {}, proving took {:?}",
+            cycles, proving_duration
+        );
+
+        hooks
+            .on_proving_end(
+                public_values.block_number,
+                ProofProgram::Aggregation,
+                &bincode::serialize(&proof.proof)?,
+                &bincode::serialize(&proof.public_values)?,
+                &proof.zkm_version,
+                &self.vk,
+                cycles,
+                proving_duration,
+            )
+            .await?;
+
+        Ok(proof)
+    }
+}
+
+impl<P> Debug for Aggregator<P> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Aggregator").field("elf_id", &self.elf_id).finish()
+    }
+}
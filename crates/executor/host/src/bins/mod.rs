@@ -1 +1,3 @@
+pub mod persist_proof_hook;
 pub mod persist_report_hook;
+pub mod solidity_fixture_hook;
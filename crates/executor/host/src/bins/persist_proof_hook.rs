@@ -0,0 +1,85 @@
+use std::{path::PathBuf, time::Duration};
+
+use tracing::info;
+use zkm_sdk::{HashableKey, ZKMProof, ZKMPublicValues, ZKMVerifyingKey};
+
+use crate::{
+    decode_aggregation_public_values, decode_public_values, ExecutionHooks, ProofArtifact,
+    ProofKind, ProofProgram,
+};
+
+/// Persists the generated proofs as [ProofArtifact]s in a directory.
+#[derive(Debug)]
+pub struct PersistProof {
+    proof_dir: PathBuf,
+}
+
+impl PersistProof {
+    pub fn new(proof_dir: PathBuf) -> Self {
+        Self { proof_dir }
+    }
+}
+
+impl ExecutionHooks for PersistProof {
+    async fn on_proving_end(
+        &self,
+        _block_number: u64,
+        program: ProofProgram,
+        proof_bytes: &[u8],
+        public_values_bytes: &[u8],
+        zkm_version: &str,
+        vk: &ZKMVerifyingKey,
+        cycles: u64,
+        proving_duration: Duration,
+    ) -> eyre::Result<()> {
+        let proof: ZKMProof = bincode::deserialize(proof_bytes)?;
+        let public_values: ZKMPublicValues = bincode::deserialize(public_values_bytes)?;
+        // The artifact is described by the proven public values rather than by the caller.
+        let (chain_id, from_block, to_block, block_hash) = match program {
+            ProofProgram::Block => {
+                let public_values = decode_public_values(&public_values)?;
+                (
+                    public_values.chain_id,
+                    public_values.first_block_number,
+                    public_values.block_number,
+                    public_values.block_hash,
+                )
+            }
+            ProofProgram::Aggregation => {
+                let public_values = decode_aggregation_public_values(&public_values)?;
+                (
+                    public_values.chain_id,
+                    public_values.first_block_number,
+                    public_values.block_number,
+                    public_values.block_hash,
+                )
+            }
+        };
+
+        let artifact = ProofArtifact {
+            chain_id,
+            from_block,
+            to_block,
+            block_hash,
+            program,
+            vk_hash: vk.bytes32(),
+            proof_kind: ProofKind::from(&proof),
+            zkm_version: zkm_version.to_string(),
+            cycles,
+            proving_duration,
+            proof: proof_bytes.to_vec(),
+            public_values: public_values_bytes.to_vec(),
+        };
+
+        let path = artifact.save(&self.proof_dir)?;
+        info!(
+            "{:?} proof for blocks {}-{} saved to {}",
+            artifact.proof_kind,
+            artifact.from_block,
+            artifact.to_block,
+            path.display()
+        );
+
+        Ok(())
+    }
+}
@@ -1,10 +1,14 @@
 use std::{
     fs::{File, OpenOptions},
+    io::ErrorKind,
     path::PathBuf,
+    sync::OnceLock,
+    time::Duration,
 };
 
 use alloy_consensus::{Block, BlockHeader};
-use csv::{Writer, WriterBuilder};
+use clap::ValueEnum;
+use csv::{ReaderBuilder, Writer, WriterBuilder};
 use guest_executor::executor::{
     BLOCK_EXECUTION, COMPUTE_STATE_ROOT, DESERIALZE_INPUTS, INIT_WITNESS_DB, RECOVER_SENDERS,
     VALIDATE_EXECUTION,
@@ -13,10 +17,11 @@ use reth_primitives_traits::{BlockBody, NodePrimitives};
 use revm_bytecode::opcode::OPCODE_INFO;
 use serde::{Deserialize, Serialize};
 use strum::IntoEnumIterator;
+use tracing::warn;
 use zkm_core_executor::syscalls::SyscallCode;
-use zkm_sdk::ExecutionReport;
+use zkm_sdk::{ExecutionReport, ZKMProof, ZKMVerifyingKey};
 
-use crate::ExecutionHooks;
+use crate::{ExecutionHooks, ProofKind, ProofProgram};
 
 const PRECOMPILES: [&str; 10] = [
     "ecrecover",
@@ -36,6 +41,7 @@ const PRECOMPILES: [&str; 10] = [
 struct ExecutionReportData {
     chain_id: u64,
     block_number: u64,
+    proof_kind: String,
     gas_used: u64,
     tx_count: usize,
     number_cycles: u64,
@@ -54,6 +60,8 @@ pub struct PersistExecutionReport {
     report_path: PathBuf,
     precompile_tracking: bool,
     opcode_tracking: bool,
+    /// The file the records are appended to, resolved on the first write.
+    resolved_path: OnceLock<PathBuf>,
 }
 
 impl PersistExecutionReport {
@@ -63,13 +71,20 @@ impl PersistExecutionReport {
         precompile_tracking: bool,
         opcode_tracking: bool,
     ) -> Self {
-        Self { chain_id, report_path, precompile_tracking, opcode_tracking }
+        Self {
+            chain_id,
+            report_path,
+            precompile_tracking,
+            opcode_tracking,
+            resolved_path: OnceLock::new(),
+        }
     }
 
-    fn write_header(&self, writer: &mut Writer<File>) -> csv::Result<()> {
+    fn headers(&self) -> Vec<String> {
         let mut headers = vec![
             "chain_id".to_string(),
             "block_number".to_string(),
+            "proof_kind".to_string(),
             "gas_used".to_string(),
             "tx_count".to_string(),
         ];
@@ -114,7 +129,67 @@ impl PersistExecutionReport {
             }
         }
 
-        writer.write_record(&headers)
+        headers
+    }
+
+    /// Returns the file the records are appended to: the report path, unless it already has other
+    /// columns (e.g. written by an older version or with other tracking options), in which case
+    /// the first file `{stem}-{n}.{extension}` that is new or has the same columns.
+    fn path(&self) -> eyre::Result<PathBuf> {
+        if let Some(path) = self.resolved_path.get() {
+            return Ok(path.clone());
+        }
+
+        let headers = self.headers();
+        let mut path = self.report_path.clone();
+        for n in 1.. {
+            let file = match File::open(&path) {
+                Ok(file) => file,
+                Err(err) if err.kind() == ErrorKind::NotFound => break,
+                Err(err) => return Err(err.into()),
+            };
+            let mut reader = ReaderBuilder::new().has_headers(false).from_reader(file);
+            let same_columns = match reader.records().next() {
+                Some(record) => record?.iter().eq(headers.iter().map(String::as_str)),
+                None => true,
+            };
+            if same_columns {
+                break;
+            }
+
+            let stem = self.report_path.file_stem().unwrap_or_default().to_string_lossy();
+            let name = match self.report_path.extension() {
+                Some(extension) => format!("{stem}-{n}.{}", extension.to_string_lossy()),
+                None => format!("{stem}-{n}"),
+            };
+            path = self.report_path.with_file_name(name);
+        }
+
+        if path != self.report_path {
+            warn!(
+                "{} has other columns, the execution report is written to {}",
+                self.report_path.display(),
+                path.display()
+            );
+        }
+
+        Ok(self.resolved_path.get_or_init(|| path).clone())
+    }
+
+    /// Opens the report file for appending, writing the header if the file is new.
+    fn open(&self) -> eyre::Result<Writer<File>> {
+        // Open the file for appending or create it if it doesn't exist
+        let file = OpenOptions::new().append(true).create(true).open(self.path()?)?;
+
+        // Check if the file is empty
+        let file_is_empty = file.metadata()?.len() == 0;
+        let mut writer = WriterBuilder::new().from_writer(file);
+
+        if file_is_empty {
+            writer.write_record(self.headers())?;
+        }
+
+        Ok(writer)
     }
 
     fn write_record<P: NodePrimitives>(
@@ -126,6 +201,8 @@ impl PersistExecutionReport {
         let mut record = vec![
             self.chain_id.to_string(),
             block.number.to_string(),
+            // The block was only executed.
+            "none".to_string(),
             block.header.gas_used().to_string(),
             block.body.transaction_count().to_string(),
         ];
@@ -198,21 +275,83 @@ impl ExecutionHooks for PersistExecutionReport {
     ) -> eyre::Result<()> {
         println!("\nExecution report:\n{execution_report}");
 
-        // Open the file for appending or create it if it doesn't exist
-        let file = OpenOptions::new().append(true).create(true).open(self.report_path.clone())?;
+        let mut writer = self.open()?;
+        self.write_record::<P>(&mut writer, executed_block, execution_report)?;
+        writer.flush()?;
 
-        // Check if the file is empty
-        let file_is_empty = file.metadata()?.len() == 0;
-        let mut writer = WriterBuilder::new().from_writer(file);
+        Ok(())
+    }
 
-        if file_is_empty {
-            self.write_header(&mut writer)?;
+    async fn on_proving_end(
+        &self,
+        block_number: u64,
+        program: ProofProgram,
+        proof_bytes: &[u8],
+        _public_values_bytes: &[u8],
+        _zkm_version: &str,
+        _vk: &ZKMVerifyingKey,
+        cycles: u64,
+        _proving_duration: Duration,
+    ) -> eyre::Result<()> {
+        if program != ProofProgram::Block {
+            return Ok(());
         }
 
-        self.write_record::<P>(&mut writer, executed_block, execution_report)?;
+        let proof: ZKMProof = bincode::deserialize(proof_bytes)?;
+        let proof_kind = ProofKind::from(&proof);
+
+        // The proving only reports the total cycle count, the other columns are left empty.
+        let mut record = vec![
+            self.chain_id.to_string(),
+            block_number.to_string(),
+            proof_kind.to_possible_value().expect("no skipped variant").get_name().to_string(),
+            String::new(),
+            String::new(),
+        ];
+        if !self.opcode_tracking {
+            record.push(cycles.to_string());
+        }
+        record.resize(self.headers().len(), String::new());
 
+        let mut writer = self.open()?;
+        writer.write_record(&record)?;
         writer.flush()?;
 
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_report_with_other_columns_is_kept() {
+        let dir = std::env::temp_dir().join(format!("execution-report-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let report_path = dir.join("report.csv");
+        std::fs::write(&report_path, "chain_id,block_number,gas_used\n1,2,3\n").unwrap();
+
+        let hook = PersistExecutionReport::new(1, report_path.clone(), false, false);
+        let mut record = vec![String::new(); hook.headers().len()];
+        record[..3].clone_from_slice(&["1".to_string(), "3".to_string(), "none".to_string()]);
+        let mut writer = hook.open().unwrap();
+        writer.write_record(&record).unwrap();
+        writer.flush().unwrap();
+
+        assert_eq!(
+            std::fs::read_to_string(&report_path).unwrap(),
+            "chain_id,block_number,gas_used\n1,2,3\n"
+        );
+        let new_path = dir.join("report-1.csv");
+        let report = std::fs::read_to_string(&new_path).unwrap();
+        assert_eq!(report.lines().next().unwrap(), hook.headers().join(","));
+        assert_eq!(report.lines().nth(1).unwrap(), record.join(","));
+
+        // A report with the same columns is appended to.
+        let hook = PersistExecutionReport::new(1, report_path, false, false);
+        assert_eq!(hook.path().unwrap(), new_path);
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
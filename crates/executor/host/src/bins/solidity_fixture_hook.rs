@@ -0,0 +1,111 @@
+use std::{path::PathBuf, time::Duration};
+
+use alloy_primitives::{hex, B256};
+use serde::{Deserialize, Serialize};
+use tracing::info;
+use zkm_sdk::{HashableKey, ZKMProof, ZKMProofWithPublicValues, ZKMPublicValues, ZKMVerifyingKey};
+
+use crate::{decode_aggregation_public_values, decode_public_values, ExecutionHooks, ProofProgram};
+
+/// A fixture for testing an on-chain verifier contract with Foundry.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SolidityFixture {
+    pub vkey: String,
+    pub public_values: String,
+    pub proof: String,
+    pub chain_id: u64,
+    pub chain_config_hash: B256,
+    pub parent_hash: B256,
+    pub block_hash: B256,
+    pub block_number: u64,
+    pub prev_state_root: B256,
+    pub state_root: B256,
+}
+
+/// Exports the generated Groth16 and PLONK proofs as [SolidityFixture]s in a directory.
+#[derive(Debug)]
+pub struct ExportSolidityFixture {
+    fixture_dir: PathBuf,
+}
+
+impl ExportSolidityFixture {
+    pub fn new(fixture_dir: PathBuf) -> Self {
+        Self { fixture_dir }
+    }
+}
+
+impl ExecutionHooks for ExportSolidityFixture {
+    async fn on_proving_end(
+        &self,
+        block_number: u64,
+        program: ProofProgram,
+        proof_bytes: &[u8],
+        public_values_bytes: &[u8],
+        zkm_version: &str,
+        vk: &ZKMVerifyingKey,
+        _cycles: u64,
+        _proving_duration: Duration,
+    ) -> eyre::Result<()> {
+        let proof: ZKMProof = bincode::deserialize(proof_bytes)?;
+        let kind = match proof {
+            ZKMProof::Groth16(_) => "groth16",
+            ZKMProof::Plonk(_) => "plonk",
+            _ => {
+                info!("Skipping the Solidity fixture export, only wrapped proofs are supported");
+                return Ok(());
+            }
+        };
+
+        let public_values: ZKMPublicValues = bincode::deserialize(public_values_bytes)?;
+        let mut fixture = match program {
+            ProofProgram::Block => {
+                let decoded = decode_public_values(&public_values)?;
+                SolidityFixture {
+                    chain_id: decoded.chain_id,
+                    chain_config_hash: decoded.chain_config_hash,
+                    parent_hash: decoded.parent_hash,
+                    block_hash: decoded.block_hash,
+                    block_number: decoded.block_number,
+                    prev_state_root: decoded.prev_state_root,
+                    state_root: decoded.state_root,
+                    ..Default::default()
+                }
+            }
+            ProofProgram::Aggregation => {
+                let decoded = decode_aggregation_public_values(&public_values)?;
+                SolidityFixture {
+                    chain_id: decoded.chain_id,
+                    chain_config_hash: decoded.chain_config_hash,
+                    parent_hash: decoded.parent_hash,
+                    block_hash: decoded.block_hash,
+                    block_number: decoded.block_number,
+                    prev_state_root: decoded.prev_state_root,
+                    state_root: decoded.state_root,
+                    ..Default::default()
+                }
+            }
+        };
+
+        let proof =
+            ZKMProofWithPublicValues { proof, public_values, zkm_version: zkm_version.to_string() };
+        fixture.vkey = vk.bytes32();
+        fixture.public_values = hex::encode_prefixed(proof.public_values.as_slice());
+        fixture.proof = hex::encode_prefixed(proof.bytes());
+
+        let prefix = match program {
+            ProofProgram::Block => "",
+            ProofProgram::Aggregation => "aggregation-",
+        };
+        let fixture_path = self
+            .fixture_dir
+            .join(format!("{}/{prefix}{block_number}-{kind}.json", fixture.chain_id));
+        if let Some(parent) = fixture_path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(&fixture_path, serde_json::to_string_pretty(&fixture)?)?;
+        info!("Solidity fixture for block {} saved to {}", block_number, fixture_path.display());
+
+        Ok(())
+    }
+}
@@ -5,6 +5,8 @@ use reth_errors::BlockExecutionError;
 use revm_primitives::B256;
 use rpc_db::RpcDbError;
 
+use crate::HeaderDiff;
+
 #[derive(Debug, thiserror::Error)]
 pub enum Error {
     #[error("Failed to parse blocks into executor friendly format {}", .0)]
@@ -21,14 +23,20 @@ pub enum Error {
     FromProof(#[from] FromProofError),
     #[error("RPC didnt have expected block height {}", .0)]
     ExpectedBlock(u64),
-    #[error("Header Mismatch \n found {} expected {}", .0, .1)]
-    HeaderMismatch(B256, B256),
+    #[error("RPC didnt have expected block {}", .0)]
+    ExpectedBlockHash(B256),
+    #[error("Header Mismatch \n found {} expected {}\n{}", .0, .1, .2)]
+    HeaderMismatch(B256, B256, HeaderDiff),
     #[error("State root mismatch after local execution \n found {} expected {}", .0, .1)]
     StateRootMismatch(B256, B256),
     #[error("Failed to read the genesis file: {}", .0)]
     FailedToReadGenesisFile(#[from] std::io::Error),
     #[error("RPC error: {}", 0)]
     RpcDbError(#[from] RpcDbError),
+    #[error("Chain config hash mismatch \n found {} expected {}", .0, .1)]
+    ChainConfigMismatch(B256, B256),
+    #[error("Invalid public values: {0}")]
+    InvalidPublicValues(String),
     #[error("custom error: {0}")]
     Custom(String),
 }
@@ -1,12 +1,16 @@
-use std::marker::PhantomData;
+use std::{marker::PhantomData, sync::Arc};
 
 use alloy_network::Ethereum;
 use alloy_provider::Network;
 use eyre::Ok;
-use guest_executor::{custom::CustomEvmFactory, BlockValidator, IntoInput, IntoPrimitives};
+use guest_executor::{
+    custom::CustomEvmFactory,
+    executor::{ClientExecutor, EthClientExecutor, OpClientExecutor},
+    BlockValidator, FromInput, IntoInput, IntoPrimitives,
+};
 use op_alloy_network::Optimism;
 use primitives::genesis::Genesis;
-use reth_chainspec::ChainSpec;
+use reth_chainspec::{ChainSpec, EthChainSpec};
 use reth_ethereum_primitives::EthPrimitives;
 use reth_evm::ConfigureEvm;
 use reth_evm_ethereum::EthEvmConfig;
@@ -14,6 +18,7 @@ use reth_optimism_chainspec::OpChainSpec;
 use reth_optimism_evm::OpEvmConfig;
 use reth_optimism_primitives::OpPrimitives;
 use reth_primitives_traits::NodePrimitives;
+use revm_primitives::Address;
 use serde::de::DeserializeOwned;
 use zkm_prover::components::DefaultProverComponents;
 use zkm_sdk::{Prover, ProverClient};
@@ -29,15 +34,22 @@ pub trait ExecutorComponents {
         + DeserializeOwned
         + IntoPrimitives<Self::Network>
         + IntoInput
+        + FromInput
         + BlockValidator<Self::ChainSpec>;
 
     type EvmConfig: ConfigureEvm<Primitives = Self::Primitives>;
 
-    type ChainSpec;
+    type ChainSpec: EthChainSpec + 'static;
 
     type Hooks: ExecutionHooks;
 
     fn try_into_chain_spec(genesis: &Genesis) -> eyre::Result<Self::ChainSpec>;
+
+    /// Returns the executor the guest program runs, to execute the client inputs natively.
+    fn client_executor(
+        chain_spec: Arc<Self::ChainSpec>,
+        custom_beneficiary: Option<Address>,
+    ) -> ClientExecutor<Self::EvmConfig, Self::ChainSpec>;
 }
 
 #[derive(Debug, Default)]
@@ -66,6 +78,13 @@ where
         let spec = genesis.try_into()?;
         Ok(spec)
     }
+
+    fn client_executor(
+        chain_spec: Arc<ChainSpec>,
+        custom_beneficiary: Option<Address>,
+    ) -> EthClientExecutor {
+        EthClientExecutor::eth(chain_spec, custom_beneficiary)
+    }
 }
 
 #[derive(Debug, Default)]
@@ -94,4 +113,11 @@ where
         let spec = genesis.try_into()?;
         Ok(spec)
     }
+
+    fn client_executor(
+        chain_spec: Arc<OpChainSpec>,
+        _custom_beneficiary: Option<Address>,
+    ) -> OpClientExecutor {
+        OpClientExecutor::optimism(chain_spec)
+    }
 }
@@ -0,0 +1,59 @@
+use clap::Args;
+use rpc_db::{FetchConfig, DEFAULT_MAX_CONCURRENT_REQUESTS};
+
+use crate::RetryConfig;
+
+/// The arguments for the retries of the RPC requests and the fetches of the accessed state,
+/// shared by the binaries.
+#[derive(Debug, Clone, Args)]
+pub struct FetchArgs {
+    /// The maximum number of retries of a request on rate limiting or server errors.
+    #[clap(long, env, default_value_t = 3)]
+    pub rpc_max_retries: u32,
+
+    /// The initial backoff between the retries of a request, in milliseconds.
+    #[clap(long, env, default_value_t = 1000)]
+    pub rpc_initial_backoff_ms: u64,
+
+    /// The compute units per second budget of the RPCs, used to compute the backoff.
+    #[clap(long, env, default_value_t = 100)]
+    pub rpc_compute_units_per_second: u64,
+
+    /// The maximum number of concurrent RPC requests when fetching the account proofs and the
+    /// ancestor headers of a block.
+    #[clap(long, env, default_value_t = DEFAULT_MAX_CONCURRENT_REQUESTS)]
+    pub max_concurrent_requests: usize,
+
+    /// The number of `eth_getProof` calls sent per JSON-RPC batch, 1 to disable batching.
+    #[clap(long, env, default_value_t = 1)]
+    pub rpc_batch_size: usize,
+
+    /// The number of times an invalid `eth_getProof` or `eth_getCode` response is fetched again
+    /// from an RPC, before falling back to the next one.
+    #[clap(long, env, default_value_t = 1)]
+    pub invalid_proof_retries: usize,
+
+    /// Whether to prefetch the state accessed by the blocks with the `prestateTracer` of the debug
+    /// RPC before executing them, instead of fetching every account and slot on demand.
+    #[clap(long, env)]
+    pub prefetch: bool,
+}
+
+impl FetchArgs {
+    pub fn retry_config(&self) -> RetryConfig {
+        RetryConfig {
+            max_retries: self.rpc_max_retries,
+            initial_backoff_ms: self.rpc_initial_backoff_ms,
+            compute_units_per_second: self.rpc_compute_units_per_second,
+        }
+    }
+
+    pub fn fetch_config(&self) -> FetchConfig {
+        FetchConfig {
+            max_concurrent_requests: self.max_concurrent_requests,
+            batch_size: self.rpc_batch_size,
+            prefetch: self.prefetch,
+            invalid_proof_retries: self.invalid_proof_retries,
+        }
+    }
+}
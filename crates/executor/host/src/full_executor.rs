@@ -1,11 +1,20 @@
 use std::{
     fmt::{Debug, Formatter},
-    path::{Path, PathBuf},
-    sync::{Arc, OnceLock},
+    num::NonZeroUsize,
+    path::PathBuf,
+    sync::Arc,
     time::{Duration, Instant},
 };
 
-use crate::{Config, ExecutionHooks, ExecutorComponents, HostExecutor};
+use crate::{
+    decode_public_values,
+    input_cache::{
+        decode_input, encode_input, input_cache_key, input_cache_prefix, parse_input_cache_key,
+    },
+    keys::{elf_id, setup_keys},
+    Config, ExecutionHooks, ExecutorComponents, HostExecutor, InputStore, ProofProgram, RpcCache,
+};
+use alloy_network::{primitives::HeaderResponse, BlockResponse};
 use alloy_provider::Provider;
 use either::Either;
 use eyre::bail;
@@ -13,19 +22,16 @@ use guest_executor::io::ClientExecutorInput;
 use reth_primitives_traits::NodePrimitives;
 use revm_primitives::B256;
 use serde::de::DeserializeOwned;
-use sha2::{Digest, Sha256};
 use tokio::{task, time::sleep};
 use tracing::{info, info_span, warn};
 use zkm_prover::components::DefaultProverComponents;
 use zkm_sdk::{
-    ExecutionReport, Prover, ZKMProofKind, ZKMProvingKey, ZKMPublicValues, ZKMStdin,
-    ZKMVerifyingKey,
+    ExecutionReport, Prover, ZKMProofKind, ZKMProofWithPublicValues, ZKMProvingKey,
+    ZKMPublicValues, ZKMStdin, ZKMVerifyingKey,
 };
 
 pub type EitherExecutor<C, P> = Either<FullExecutor<C, P>, CachedExecutor<C>>;
 
-static ELF_ID: OnceLock<String> = OnceLock::new();
-
 pub async fn build_executor<C, P>(
     elf: Vec<u8>,
     provider: Option<P>,
@@ -47,13 +53,14 @@ where
         ));
     }
 
-    if let Some(cache_dir) = config.cache_dir {
+    if let Some(input_store) = config.input_store()? {
         return Ok(Either::Right(
             CachedExecutor::try_new(
                 elf,
                 client,
                 hooks,
-                cache_dir,
+                input_store,
+                config.key_cache_dir,
                 config.chain.id(),
                 config.prove_mode,
             )
@@ -61,12 +68,48 @@ where
         ));
     }
 
-    bail!("Either a RPC URL or a cache dir must be provided")
+    bail!("Either a RPC URL, a cache dir or an input store URL must be provided")
 }
 
 pub trait BlockExecutor<C: ExecutorComponents> {
+    /// Executes the block with the given block number, returning the proof if proving is enabled.
+    #[allow(async_fn_in_trait)]
+    async fn execute(&self, block_number: u64) -> eyre::Result<Option<ZKMProofWithPublicValues>> {
+        self.execute_batch(block_number, block_number).await
+    }
+
+    /// Executes the block with the given block number and hash, returning the proof if proving is
+    /// enabled.
+    #[allow(async_fn_in_trait)]
+    async fn execute_block_hash(
+        &self,
+        block_number: u64,
+        block_hash: B256,
+    ) -> eyre::Result<Option<ZKMProofWithPublicValues>> {
+        self.execute_pinned_batch(block_number, block_number, Some(block_hash)).await
+    }
+
+    /// Executes the blocks from `from_block` to `to_block` (inclusive) in a single guest run,
+    /// returning the proof if proving is enabled.
     #[allow(async_fn_in_trait)]
-    async fn execute(&self, block_number: u64) -> eyre::Result<()>;
+    async fn execute_batch(
+        &self,
+        from_block: u64,
+        to_block: u64,
+    ) -> eyre::Result<Option<ZKMProofWithPublicValues>> {
+        self.execute_pinned_batch(from_block, to_block, None).await
+    }
+
+    /// Executes the blocks from `from_block` to `to_block` (inclusive) in a single guest run, the
+    /// last one being the block with the hash `to_block_hash` if set and the canonical one
+    /// otherwise, returning the proof if proving is enabled.
+    #[allow(async_fn_in_trait)]
+    async fn execute_pinned_batch(
+        &self,
+        from_block: u64,
+        to_block: u64,
+        to_block_hash: Option<B256>,
+    ) -> eyre::Result<Option<ZKMProofWithPublicValues>>;
 
     fn client(&self) -> Arc<C::Prover>;
 
@@ -74,13 +117,19 @@ pub trait BlockExecutor<C: ExecutorComponents> {
 
     fn vk(&self) -> Arc<ZKMVerifyingKey>;
 
+    /// Returns the identifier of the ELF of the executor, i.e. its hex encoded SHA-256 digest.
+    fn elf_id(&self) -> String;
+
     #[allow(async_fn_in_trait)]
     async fn process_client(
         &self,
         client_input: ClientExecutorInput<C::Primitives>,
         hooks: &C::Hooks,
         prove_mode: Option<ZKMProofKind>,
-    ) -> eyre::Result<()> {
+    ) -> eyre::Result<Option<ZKMProofWithPublicValues>> {
+        // The hooks are notified of the last block, whose hash the proof commits, so that the
+        // reports and artifacts of a range are attributed to the block they end with.
+        let block_number = client_input.last_block().header.number;
         let mut stdin = ZKMStdin::new();
         let buffer = bincode::serialize(&client_input).unwrap();
 
@@ -91,10 +140,10 @@ pub trait BlockExecutor<C: ExecutorComponents> {
             info!("Starting proof generation");
 
             let proving_start = Instant::now();
-            hooks.on_proving_start(client_input.current_block.number).await?;
+            hooks.on_proving_start(block_number).await?;
             let client = self.client();
             let pk = self.pk();
-            let elf_id = Some(ELF_ID.get().unwrap().clone());
+            let elf_id = Some(self.elf_id());
             info!("elf id: {:?}", elf_id);
 
             let proof_with_cycles = task::spawn_blocking(move || {
@@ -114,7 +163,8 @@ pub trait BlockExecutor<C: ExecutorComponents> {
 
             hooks
                 .on_proving_end(
-                    client_input.current_block.number,
+                    block_number,
+                    ProofProgram::Block,
                     &proof_bytes,
                     &public_values_bytes,
                     &proof_with_cycles.0.zkm_version,
@@ -126,8 +176,10 @@ pub trait BlockExecutor<C: ExecutorComponents> {
 
             info!(
                 "Proof for block {} successfully generated! Proving took {:?}",
-                client_input.current_block.number, proving_duration
+                block_number, proving_duration
             );
+
+            Ok(Some(proof_with_cycles.0))
         } else {
             // Execute the block inside the zkVM.
             crate::utils::zkm_dump(&self.pk().elf, &stdin, client_input.current_block.number);
@@ -136,21 +188,26 @@ pub trait BlockExecutor<C: ExecutorComponents> {
             let (_, execute_result) =
                 execute_client(client_input.current_block.number, self.client(), self.pk(), stdin)
                     .await?;
-            let (mut public_values, execution_report) = execute_result?;
+            let (public_values, execution_report) = execute_result?;
 
             let cycles: u64 = execution_report.cycle_tracker.values().sum();
             info!("total cycles: {:?}", cycles);
 
-            // Read the block hash.
-            let block_hash = public_values.read::<B256>();
-            info!(?block_hash, "Execution successful");
+            // Decode the public values.
+            let public_values = decode_public_values(&public_values)?;
+            info!(
+                parent_hash = ?public_values.parent_hash,
+                block_hash = ?public_values.block_hash,
+                state_root = ?public_values.state_root,
+                "Execution successful"
+            );
 
             hooks
-                .on_execution_end::<C::Primitives>(&client_input.current_block, &execution_report)
+                .on_execution_end::<C::Primitives>(client_input.last_block(), &execution_report)
                 .await?;
-        }
 
-        Ok(())
+            Ok(None)
+        }
     }
 }
 
@@ -159,10 +216,19 @@ where
     C: ExecutorComponents,
     P: Provider<C::Network> + Clone + std::fmt::Debug,
 {
-    async fn execute(&self, block_number: u64) -> eyre::Result<()> {
+    async fn execute_pinned_batch(
+        &self,
+        from_block: u64,
+        to_block: u64,
+        to_block_hash: Option<B256>,
+    ) -> eyre::Result<Option<ZKMProofWithPublicValues>> {
         match self {
-            Either::Left(ref executor) => executor.execute(block_number).await,
-            Either::Right(ref executor) => executor.execute(block_number).await,
+            Either::Left(ref executor) => {
+                executor.execute_pinned_batch(from_block, to_block, to_block_hash).await
+            }
+            Either::Right(ref executor) => {
+                executor.execute_pinned_batch(from_block, to_block, to_block_hash).await
+            }
         }
     }
 
@@ -186,6 +252,13 @@ where
             Either::Right(ref executor) => executor.vk.clone(),
         }
     }
+
+    fn elf_id(&self) -> String {
+        match self {
+            Either::Left(ref executor) => executor.elf_id.clone(),
+            Either::Right(ref executor) => executor.elf_id.clone(),
+        }
+    }
 }
 
 pub struct FullExecutor<C, P>
@@ -199,7 +272,9 @@ where
     client: Arc<C::Prover>,
     pk: Arc<ZKMProvingKey>,
     vk: Arc<ZKMVerifyingKey>,
+    elf_id: String,
     hooks: C::Hooks,
+    input_store: Option<Arc<dyn InputStore>>,
     config: Config,
 }
 
@@ -217,16 +292,9 @@ where
         hooks: C::Hooks,
         config: Config,
     ) -> eyre::Result<Self> {
-        let cloned_client = client.clone();
-
-        // Setup the proving key and verification key.
-        let (pk, vk) = task::spawn_blocking(move || {
-            let (pk, vk) = cloned_client.setup(&elf);
-            (pk, vk)
-        })
-        .await?;
-
-        ELF_ID.set(hex::encode(Sha256::digest(&pk.elf))).unwrap();
+        let (pk, vk) = setup_keys(client.clone(), elf, config.key_cache_dir.as_deref()).await?;
+        let elf_id = elf_id(&pk.elf);
+        let input_store = config.input_store()?;
 
         Ok(Self {
             provider,
@@ -234,11 +302,18 @@ where
             host_executor: HostExecutor::new(
                 evm_config,
                 Arc::new(C::try_into_chain_spec(&config.genesis)?),
-            ),
+            )
+            .with_fetch_config(config.fetch_config)
+            .with_rpc_cache(NonZeroUsize::new(config.rpc_cache_size).map(RpcCache::new))
+            .with_witness_source(config.witness_source)
+            .with_state_mismatch_diagnostics(config.diagnose_state_mismatch)
+            .with_rpc_urls(config.rpc_url.as_ref(), config.debug_rpc_url.as_ref()),
             client,
             pk: Arc::new(pk),
             vk: Arc::new(vk),
+            elf_id,
             hooks,
+            input_store,
             config,
         })
     }
@@ -258,64 +333,27 @@ where
     C: ExecutorComponents,
     P: Provider<C::Network> + Clone + std::fmt::Debug,
 {
-    async fn execute(&self, block_number: u64) -> eyre::Result<()> {
-        self.hooks.on_execution_start(block_number).await?;
-
-        let client_input_from_cache = self.config.cache_dir.as_ref().and_then(|cache_dir| {
-            match try_load_input_from_cache::<C::Primitives>(
-                cache_dir,
-                self.config.chain.id(),
-                block_number,
-            ) {
-                Ok(client_input) => client_input,
-                Err(e) => {
-                    warn!("Failed to load input from cache: {}", e);
-                    None
-                }
-            }
-        });
-
-        let now = Instant::now();
-        let client_input = match client_input_from_cache {
-            Some(mut client_input_from_cache) => {
-                // Override opcode tracking from cache by the setting provided by the user
-                client_input_from_cache.opcode_tracking = self.config.opcode_tracking;
-                client_input_from_cache
-            }
-            None => {
-                // Execute the host.
-                let client_input = self
-                    .host_executor
-                    .execute(
-                        block_number,
-                        &self.provider,
-                        &self.debug_provider,
-                        self.config.genesis.clone(),
-                        self.config.custom_beneficiary,
-                        self.config.opcode_tracking,
-                    )
-                    .await?;
-
-                if let Some(ref cache_dir) = self.config.cache_dir {
-                    let input_folder = cache_dir.join(format!("input/{}", self.config.chain.id()));
-                    if !input_folder.exists() {
-                        std::fs::create_dir_all(&input_folder)?;
-                    }
-
-                    let input_path = input_folder.join(format!("{block_number}.bin"));
-                    let mut cache_file = std::fs::File::create(input_path)?;
-
-                    bincode::serialize_into(&mut cache_file, &client_input)?;
-                }
-
-                client_input
-            }
-        };
-        info!("Block {} executed in {:?}", block_number, now.elapsed());
-
-        self.process_client(client_input, &self.hooks, self.config.prove_mode).await?;
+    async fn execute_pinned_batch(
+        &self,
+        from_block: u64,
+        to_block: u64,
+        to_block_hash: Option<B256>,
+    ) -> eyre::Result<Option<ZKMProofWithPublicValues>> {
+        self.hooks.on_execution_start(from_block).await?;
+
+        let client_input = load_or_generate_input::<C, P>(
+            &self.host_executor,
+            &self.provider,
+            &self.debug_provider,
+            self.input_store.as_deref(),
+            &self.config,
+            from_block,
+            to_block,
+            to_block_hash,
+        )
+        .await?;
 
-        Ok(())
+        self.process_client(client_input, &self.hooks, self.config.prove_mode).await
     }
 
     fn client(&self) -> Arc<C::Prover> {
@@ -329,6 +367,10 @@ where
     fn vk(&self) -> Arc<ZKMVerifyingKey> {
         self.vk.clone()
     }
+
+    fn elf_id(&self) -> String {
+        self.elf_id.clone()
+    }
 }
 
 impl<C, P> Debug for FullExecutor<C, P>
@@ -337,7 +379,10 @@ where
     P: Provider<C::Network> + Clone + std::fmt::Debug,
 {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        f.debug_struct("FullExecutor").field("config", &self.config).finish()
+        f.debug_struct("FullExecutor")
+            .field("elf_id", &self.elf_id)
+            .field("config", &self.config)
+            .finish()
     }
 }
 
@@ -345,11 +390,12 @@ pub struct CachedExecutor<C>
 where
     C: ExecutorComponents,
 {
-    cache_dir: PathBuf,
+    input_store: Arc<dyn InputStore>,
     chain_id: u64,
     client: Arc<C::Prover>,
     pk: Arc<ZKMProvingKey>,
     vk: Arc<ZKMVerifyingKey>,
+    elf_id: String,
     hooks: C::Hooks,
     prove_mode: Option<ZKMProofKind>,
 }
@@ -362,25 +408,21 @@ where
         elf: Vec<u8>,
         client: Arc<C::Prover>,
         hooks: C::Hooks,
-        cache_dir: PathBuf,
+        input_store: Arc<dyn InputStore>,
+        key_cache_dir: Option<PathBuf>,
         chain_id: u64,
         prove_mode: Option<ZKMProofKind>,
     ) -> eyre::Result<Self> {
-        let cloned_client = client.clone();
-
-        // Setup the proving key and verification key.
-        let (pk, vk) = task::spawn_blocking(move || {
-            let (pk, vk) = cloned_client.setup(&elf);
-            (pk, vk)
-        })
-        .await?;
+        let (pk, vk) = setup_keys(client.clone(), elf, key_cache_dir.as_deref()).await?;
+        let elf_id = elf_id(&pk.elf);
 
         Ok(Self {
-            cache_dir,
+            input_store,
             chain_id,
             client,
             pk: Arc::new(pk),
             vk: Arc::new(vk),
+            elf_id,
             hooks,
             prove_mode,
         })
@@ -391,13 +433,20 @@ impl<C> BlockExecutor<C> for CachedExecutor<C>
 where
     C: ExecutorComponents,
 {
-    async fn execute(&self, block_number: u64) -> eyre::Result<()> {
-        let client_input = try_load_input_from_cache::<C::Primitives>(
-            &self.cache_dir,
+    async fn execute_pinned_batch(
+        &self,
+        from_block: u64,
+        to_block: u64,
+        to_block_hash: Option<B256>,
+    ) -> eyre::Result<Option<ZKMProofWithPublicValues>> {
+        let client_input = load_cached_input::<C::Primitives>(
+            self.input_store.as_ref(),
             self.chain_id,
-            block_number,
-        )?
-        .ok_or(eyre::eyre!("No cached input found"))?;
+            from_block,
+            to_block,
+            to_block_hash,
+        )
+        .await?;
 
         self.process_client(client_input, &self.hooks, self.prove_mode).await
     }
@@ -413,6 +462,10 @@ where
     fn vk(&self) -> Arc<ZKMVerifyingKey> {
         self.vk.clone()
     }
+
+    fn elf_id(&self) -> String {
+        self.elf_id.clone()
+    }
 }
 
 impl<C> Debug for CachedExecutor<C>
@@ -420,7 +473,10 @@ where
     C: ExecutorComponents,
 {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        f.debug_struct("CachedExecutor").field("cache_dir", &self.cache_dir).finish()
+        f.debug_struct("CachedExecutor")
+            .field("elf_id", &self.elf_id)
+            .field("input_store", &self.input_store)
+            .finish()
     }
 }
 
@@ -441,20 +497,190 @@ async fn execute_client<P: Prover<DefaultProverComponents> + 'static>(
     .map_err(|err| eyre::eyre!("{err}"))
 }
 
-fn try_load_input_from_cache<P: NodePrimitives + DeserializeOwned>(
-    cache_dir: &Path,
+/// Returns the client input of a block range, loaded from the input store if it is cached there,
+/// and otherwise generated by executing the blocks on the host and stored.
+#[allow(clippy::too_many_arguments)]
+pub(crate) async fn load_or_generate_input<C, P>(
+    host_executor: &HostExecutor<C::EvmConfig, C::ChainSpec>,
+    provider: &P,
+    debug_provider: &P,
+    input_store: Option<&dyn InputStore>,
+    config: &Config,
+    from_block: u64,
+    to_block: u64,
+    to_block_hash: Option<B256>,
+) -> eyre::Result<ClientExecutorInput<C::Primitives>>
+where
+    C: ExecutorComponents,
+    P: Provider<C::Network> + Clone + std::fmt::Debug,
+{
+    // The last block is pinned by its hash, so that a reorg during the execution cannot mix
+    // data from two forks, and the cached inputs are keyed by it.
+    let block_hash = match to_block_hash {
+        Some(block_hash) => block_hash,
+        None => provider
+            .get_block_by_number(to_block.into())
+            .await?
+            .map(|block| block.header().hash())
+            .ok_or_else(|| eyre::eyre!("Block {to_block} not found"))?,
+    };
+
+    let client_input_from_cache = match input_store {
+        Some(input_store) => {
+            match try_load_input_from_cache::<C::Primitives>(
+                input_store,
+                config.chain.id(),
+                from_block,
+                to_block,
+                block_hash,
+                true,
+            )
+            .await
+            {
+                Ok(client_input) => client_input,
+                Err(e) => {
+                    warn!("Failed to load input from cache: {}", e);
+                    None
+                }
+            }
+        }
+        None => None,
+    };
+
+    let now = Instant::now();
+    let client_input = match client_input_from_cache {
+        Some(mut client_input_from_cache) => {
+            // Override opcode tracking from cache by the setting provided by the user
+            client_input_from_cache.opcode_tracking = config.opcode_tracking;
+            client_input_from_cache
+        }
+        None => {
+            // Execute the host.
+            let client_input = host_executor
+                .execute_range(
+                    from_block,
+                    to_block,
+                    Some(block_hash),
+                    provider,
+                    debug_provider,
+                    config.genesis.clone(),
+                    config.custom_beneficiary,
+                    config.opcode_tracking,
+                )
+                .await?;
+
+            if let Some(input_store) = input_store {
+                let entry = encode_input(
+                    config.chain.id(),
+                    from_block,
+                    to_block,
+                    &client_input,
+                    config.compress_input_cache,
+                )?;
+                let key = input_cache_key(config.chain.id(), from_block, to_block, block_hash);
+                input_store.put(&key, entry).await?;
+            }
+
+            client_input
+        }
+    };
+    if from_block == to_block {
+        info!("Block {} executed in {:?}", from_block, now.elapsed());
+    } else {
+        info!("Blocks {}-{} executed in {:?}", from_block, to_block, now.elapsed());
+    }
+
+    Ok(client_input)
+}
+
+/// Loads the cached input of a block range, if any.
+///
+/// A stale or corrupted entry is pruned when `prune` is set, so that it gets regenerated, and
+/// reported as an error otherwise.
+pub(crate) async fn try_load_input_from_cache<P: NodePrimitives + DeserializeOwned>(
+    input_store: &dyn InputStore,
     chain_id: u64,
-    block_number: u64,
+    from_block: u64,
+    to_block: u64,
+    block_hash: B256,
+    prune: bool,
 ) -> eyre::Result<Option<ClientExecutorInput<P>>> {
-    let cache_path = cache_dir.join(format!("input/{chain_id}/{block_number}.bin"));
+    let key = input_cache_key(chain_id, from_block, to_block, block_hash);
 
-    if cache_path.exists() {
-        // TODO: prune the cache if invalid instead
-        let mut cache_file = std::fs::File::open(cache_path)?;
-        let client_input = bincode::deserialize_from(&mut cache_file)?;
+    let Some(entry) = input_store.get(&key).await? else {
+        return Ok(None);
+    };
 
-        Ok(Some(client_input))
-    } else {
-        Ok(None)
+    match decode_input(&entry, chain_id, from_block, to_block, block_hash) {
+        Ok(client_input) => Ok(Some(client_input)),
+        Err(err) if prune => {
+            warn!("Pruning the cached input {}: {}", key, err);
+            input_store.delete(&key).await?;
+
+            Ok(None)
+        }
+        Err(err) => bail!("Invalid cached input {}: {}", key, err),
+    }
+}
+
+/// Loads the cached input of a block range without an RPC, the last block being the one with the
+/// hash `to_block_hash` if set.
+///
+/// Otherwise the cached inputs of the range are listed, and the only valid one is loaded: the
+/// hash of the last block is then required only if the inputs of several forks are cached.
+pub(crate) async fn load_cached_input<P: NodePrimitives + DeserializeOwned>(
+    input_store: &dyn InputStore,
+    chain_id: u64,
+    from_block: u64,
+    to_block: u64,
+    to_block_hash: Option<B256>,
+) -> eyre::Result<ClientExecutorInput<P>> {
+    if let Some(block_hash) = to_block_hash {
+        return try_load_input_from_cache(
+            input_store,
+            chain_id,
+            from_block,
+            to_block,
+            block_hash,
+            false,
+        )
+        .await?
+        .ok_or(eyre::eyre!("No cached input found"));
+    }
+
+    let mut client_inputs = Vec::new();
+    for key in input_store.list(&input_cache_prefix(chain_id, from_block, to_block)).await? {
+        let Some(block_hash) = parse_input_cache_key(&key, chain_id, from_block, to_block) else {
+            continue;
+        };
+
+        match try_load_input_from_cache(
+            input_store,
+            chain_id,
+            from_block,
+            to_block,
+            block_hash,
+            false,
+        )
+        .await
+        {
+            Ok(Some(client_input)) => client_inputs.push((block_hash, client_input)),
+            Ok(None) => {}
+            Err(err) => warn!("{}", err),
+        }
+    }
+
+    match client_inputs.len() {
+        0 => bail!("No cached input found"),
+        1 => Ok(client_inputs.pop().expect("one input is cached").1),
+        _ => bail!(
+            "The inputs of several forks are cached for block {}, its hash is required to pick one: {}",
+            to_block,
+            client_inputs
+                .iter()
+                .map(|(block_hash, _)| block_hash.to_string())
+                .collect::<Vec<_>>()
+                .join(", ")
+        ),
     }
 }
@@ -4,6 +4,14 @@ use alloy_consensus::Block;
 use reth_primitives_traits::NodePrimitives;
 use zkm_sdk::{ExecutionReport, ZKMVerifyingKey};
 
+use crate::ProofProgram;
+
+/// Hooks called around the execution and the proving of the blocks.
+///
+/// When a range of blocks is executed in a single guest run, the execution start is notified of
+/// the first block, and the other hooks of the last one, whose hash the public values commit. The
+/// proofs of the aggregation program are notified to the proving hooks too, with the last
+/// aggregated block.
 pub trait ExecutionHooks: Send {
     fn on_execution_start(
         &self,
@@ -28,6 +36,7 @@ pub trait ExecutionHooks: Send {
     fn on_proving_end(
         &self,
         _block_number: u64,
+        _program: ProofProgram,
         _proof_bytes: &[u8],
         _public_values_bytes: &[u8],
         _zkm_version: &str,
@@ -40,3 +49,133 @@ pub trait ExecutionHooks: Send {
 }
 
 impl ExecutionHooks for () {}
+
+impl<H: ExecutionHooks> ExecutionHooks for Option<H> {
+    fn on_execution_start(
+        &self,
+        block_number: u64,
+    ) -> impl Future<Output = eyre::Result<()>> + Send {
+        let hook = self.as_ref().map(|hooks| hooks.on_execution_start(block_number));
+
+        async move {
+            match hook {
+                Some(hook) => hook.await,
+                None => Ok(()),
+            }
+        }
+    }
+
+    async fn on_execution_end<P: NodePrimitives>(
+        &self,
+        executed_block: &Block<P::SignedTx>,
+        execution_report: &ExecutionReport,
+    ) -> eyre::Result<()> {
+        match self {
+            Some(hooks) => hooks.on_execution_end::<P>(executed_block, execution_report).await,
+            None => Ok(()),
+        }
+    }
+
+    async fn on_proving_start(&self, block_number: u64) -> eyre::Result<()> {
+        match self {
+            Some(hooks) => hooks.on_proving_start(block_number).await,
+            None => Ok(()),
+        }
+    }
+
+    async fn on_proving_end(
+        &self,
+        block_number: u64,
+        program: ProofProgram,
+        proof_bytes: &[u8],
+        public_values_bytes: &[u8],
+        zkm_version: &str,
+        vk: &ZKMVerifyingKey,
+        cycles: u64,
+        proving_duration: Duration,
+    ) -> eyre::Result<()> {
+        match self {
+            Some(hooks) => {
+                hooks
+                    .on_proving_end(
+                        block_number,
+                        program,
+                        proof_bytes,
+                        public_values_bytes,
+                        zkm_version,
+                        vk,
+                        cycles,
+                        proving_duration,
+                    )
+                    .await
+            }
+            None => Ok(()),
+        }
+    }
+}
+
+impl<A: ExecutionHooks, B: ExecutionHooks> ExecutionHooks for (A, B) {
+    fn on_execution_start(
+        &self,
+        block_number: u64,
+    ) -> impl Future<Output = eyre::Result<()>> + Send {
+        let first = self.0.on_execution_start(block_number);
+        let second = self.1.on_execution_start(block_number);
+
+        async move {
+            first.await?;
+            second.await
+        }
+    }
+
+    async fn on_execution_end<P: NodePrimitives>(
+        &self,
+        executed_block: &Block<P::SignedTx>,
+        execution_report: &ExecutionReport,
+    ) -> eyre::Result<()> {
+        self.0.on_execution_end::<P>(executed_block, execution_report).await?;
+        self.1.on_execution_end::<P>(executed_block, execution_report).await
+    }
+
+    async fn on_proving_start(&self, block_number: u64) -> eyre::Result<()> {
+        self.0.on_proving_start(block_number).await?;
+        self.1.on_proving_start(block_number).await
+    }
+
+    async fn on_proving_end(
+        &self,
+        block_number: u64,
+        program: ProofProgram,
+        proof_bytes: &[u8],
+        public_values_bytes: &[u8],
+        zkm_version: &str,
+        vk: &ZKMVerifyingKey,
+        cycles: u64,
+        proving_duration: Duration,
+    ) -> eyre::Result<()> {
+        self.0
+            .on_proving_end(
+                block_number,
+                program,
+                proof_bytes,
+                public_values_bytes,
+                zkm_version,
+                vk,
+                cycles,
+                proving_duration,
+            )
+            .await?;
+        self.1
+            .on_proving_end(
+                block_number,
+                program,
+                proof_bytes,
+                public_values_bytes,
+                zkm_version,
+                vk,
+                cycles,
+                proving_duration,
+            )
+            .await
+    }
+}
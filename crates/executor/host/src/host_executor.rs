@@ -1,15 +1,27 @@
-use std::sync::Arc;
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+};
 
-use crate::HostError;
+use crate::{
+    mismatch::report_receipt_divergences, state_diagnostics::report_state_root_mismatch,
+    HeaderDiff, HostError, WitnessSource,
+};
 use alloy_consensus::{BlockHeader, Header, TxReceipt};
-use alloy_network::BlockResponse;
+use alloy_eips::BlockId;
+use alloy_network::{primitives::HeaderResponse, BlockResponse};
 use alloy_primitives::{Bloom, Sealable};
 use alloy_provider::{Network, Provider};
 use guest_executor::{
     custom::CustomEvmFactory, io::ClientExecutorInput, BlockValidator, IntoInput, IntoPrimitives,
 };
 use primitives::{genesis::Genesis, is_goat_testnet};
-use reth_chainspec::ChainSpec;
+use provider::endpoint_name;
+use reth_chainspec::{ChainSpec, EthChainSpec};
+use reth_errors::ProviderError;
 use reth_evm::{
     execute::{BasicBlockExecutor, Executor},
     ConfigureEvm,
@@ -19,43 +31,108 @@ use reth_optimism_chainspec::OpChainSpec;
 use reth_optimism_evm::OpEvmConfig;
 use reth_primitives_traits::{Block, BlockBody, SealedHeader};
 use reth_trie::{HashedPostState, KeccakKeyHasher};
-use revm::database::CacheDB;
-use revm_primitives::Address;
-use rpc_db::RpcDb;
+use revm::{
+    database::{CacheDB, DatabaseRef},
+    state::Bytecode,
+};
+use revm_primitives::{Address, B256};
+use rpc_db::{BasicRpcDb, ExecutionWitnessRpcDb, FetchConfig, RpcCache, RpcDb, RpcDbError};
+use url::Url;
 
 pub type EthHostExecutor = HostExecutor<EthEvmConfig<ChainSpec, CustomEvmFactory>, ChainSpec>;
 
 pub type OpHostExecutor = HostExecutor<OpEvmConfig, OpChainSpec>;
 
+/// The number of consecutive `debug_executionWitness` failures after which the
+/// [WitnessSource::Auto] source stops trying it.
+const MAX_EXECUTION_WITNESS_FAILURES: usize = 3;
+
 /// An executor that fetches data from a [Provider] to execute blocks in the [ClientExecutor].
 #[derive(Debug, Clone)]
 pub struct HostExecutor<C: ConfigureEvm, CS> {
     evm_config: C,
     chain_spec: Arc<CS>,
+    fetch_config: FetchConfig,
+    /// The cache of the RPC data shared across the executed blocks.
+    rpc_cache: Option<RpcCache>,
+    witness_source: WitnessSource,
+    diagnose_state_mismatch: bool,
+    /// The number of consecutive `debug_executionWitness` failures of the debug provider.
+    execution_witness_failures: Arc<AtomicUsize>,
+    /// The host of the debug RPC, which the state is fetched from, if known.
+    debug_rpc_host: Option<String>,
+    /// The host of the RPC the invalid proofs and code are fetched again from, `None` if it is
+    /// the debug RPC or if the RPC URLs are not set.
+    fallback_rpc_host: Option<String>,
 }
 
 impl EthHostExecutor {
     pub fn eth(chain_spec: Arc<ChainSpec>, custom_beneficiary: Option<Address>) -> Self {
-        Self {
-            evm_config: EthEvmConfig::new_with_evm_factory(
+        Self::new(
+            EthEvmConfig::new_with_evm_factory(
                 chain_spec.clone(),
                 CustomEvmFactory::new(custom_beneficiary),
             ),
             chain_spec,
-        }
+        )
     }
 }
 
 impl OpHostExecutor {
     pub fn optimism(chain_spec: Arc<OpChainSpec>) -> Self {
-        Self { evm_config: OpEvmConfig::optimism(chain_spec.clone()), chain_spec }
+        Self::new(OpEvmConfig::optimism(chain_spec.clone()), chain_spec)
     }
 }
 
 impl<C: ConfigureEvm, CS> HostExecutor<C, CS> {
     /// Creates a new [HostExecutor].
     pub fn new(evm_config: C, chain_spec: Arc<CS>) -> Self {
-        Self { evm_config, chain_spec }
+        Self {
+            evm_config,
+            chain_spec,
+            fetch_config: FetchConfig::default(),
+            rpc_cache: None,
+            witness_source: WitnessSource::default(),
+            diagnose_state_mismatch: false,
+            execution_witness_failures: Arc::default(),
+            debug_rpc_host: None,
+            fallback_rpc_host: None,
+        }
+    }
+
+    /// Sets the concurrency and batching of the account proofs and ancestor headers fetches.
+    pub fn with_fetch_config(mut self, fetch_config: FetchConfig) -> Self {
+        self.fetch_config = fetch_config;
+        self
+    }
+
+    /// Sets the cache of the headers, bytecodes and proofs reused across the executed blocks.
+    pub fn with_rpc_cache(mut self, rpc_cache: Option<RpcCache>) -> Self {
+        self.rpc_cache = rpc_cache;
+        self
+    }
+
+    /// Sets where the witness of the accessed state is fetched from.
+    pub fn with_witness_source(mut self, witness_source: WitnessSource) -> Self {
+        self.witness_source = witness_source;
+        self
+    }
+
+    /// Sets whether to compare the touched accounts against the RPC state on a state root
+    /// mismatch, logging the diverging accounts and slots.
+    pub fn with_state_mismatch_diagnostics(mut self, diagnose_state_mismatch: bool) -> Self {
+        self.diagnose_state_mismatch = diagnose_state_mismatch;
+        self
+    }
+
+    /// Sets the URLs of the RPCs, whose hosts are reported when they serve invalid data. The
+    /// debug RPC defaults to the RPC, which isn't queried again for the invalid data it served.
+    pub fn with_rpc_urls(mut self, rpc_url: Option<&Url>, debug_rpc_url: Option<&Url>) -> Self {
+        let debug_rpc_url = debug_rpc_url.or(rpc_url);
+        self.debug_rpc_host = debug_rpc_url.map(endpoint_name);
+        self.fallback_rpc_host =
+            rpc_url.filter(|rpc_url| Some(*rpc_url) != debug_rpc_url).map(endpoint_name);
+        self
     }
 
     /// Executes the block with the given block number.
@@ -70,169 +147,425 @@ impl<C: ConfigureEvm, CS> HostExecutor<C, CS> {
     ) -> Result<ClientExecutorInput<C::Primitives>, HostError>
     where
         C::Primitives: IntoPrimitives<N> + IntoInput + BlockValidator<CS>,
+        CS: EthChainSpec,
+        P: Provider<N> + Clone + std::fmt::Debug,
+        N: Network,
+    {
+        self.execute_range(
+            block_number,
+            block_number,
+            None,
+            provider,
+            debug_provider,
+            genesis,
+            custom_beneficiary,
+            opcode_tracking,
+        )
+        .await
+    }
+
+    /// Executes the consecutive blocks from `from_block` to `to_block` (inclusive), generating a
+    /// single client input covering the whole range.
+    ///
+    /// The last block is the one with the hash `to_block_hash` if set, and the canonical one
+    /// otherwise. The previous blocks are fetched by the hash of their child, and the state by the
+    /// hash of the blocks, so that everything comes from the same fork even if a reorg happens
+    /// meanwhile.
+    #[allow(clippy::too_many_arguments)]
+    pub async fn execute_range<P, N>(
+        &self,
+        from_block: u64,
+        to_block: u64,
+        to_block_hash: Option<B256>,
+        provider: &P,
+        debug_provider: &P,
+        genesis: Genesis,
+        custom_beneficiary: Option<Address>,
+        opcode_tracking: bool,
+    ) -> Result<ClientExecutorInput<C::Primitives>, HostError>
+    where
+        C::Primitives: IntoPrimitives<N> + IntoInput + BlockValidator<CS>,
+        CS: EthChainSpec,
         P: Provider<N> + Clone + std::fmt::Debug,
         N: Network,
     {
-        let chain_id: u64 = (&genesis).try_into().unwrap();
+        // Taken from the chain spec, as custom genesis files don't map to a known chain id.
+        let chain_id = self.chain_spec.chain_id();
         tracing::debug!("chain id: {}", chain_id);
 
-        // Fetch the current block and the previous block from the provider.
-        tracing::info!("[{}] fetching the current block and the previous block", block_number);
-        let rpc_block = provider
-            .get_block_by_number(block_number.into())
-            .full()
-            .await?
-            .ok_or(HostError::ExpectedBlock(block_number))?;
+        if to_block < from_block {
+            return Err(HostError::Custom(format!(
+                "invalid block range: {from_block} is greater than {to_block}"
+            )));
+        }
 
-        let current_block = C::Primitives::into_primitive_block(rpc_block.clone());
+        // Fetch the blocks to execute and the previous block from the provider, from the last one.
+        tracing::info!("[{}] fetching the blocks and the previous block", from_block);
+        let last_block = match to_block_hash {
+            Some(block_hash) => provider
+                .get_block_by_hash(block_hash)
+                .full()
+                .await?
+                .ok_or(HostError::ExpectedBlockHash(block_hash))?,
+            None => provider
+                .get_block_by_number(to_block.into())
+                .full()
+                .await?
+                .ok_or(HostError::ExpectedBlock(to_block))?,
+        };
+        if last_block.header().number() != to_block {
+            return Err(HostError::Custom(format!(
+                "block {} is block {}, not {}",
+                last_block.header().hash(),
+                last_block.header().number(),
+                to_block
+            )));
+        }
+
+        let mut rpc_blocks = vec![last_block];
+        while rpc_blocks.len() as u64 <= to_block - from_block {
+            let parent_hash =
+                rpc_blocks.last().expect("the last block is fetched").header().parent_hash();
+            let rpc_block = provider
+                .get_block_by_hash(parent_hash)
+                .full()
+                .await?
+                .ok_or(HostError::ExpectedBlockHash(parent_hash))?;
+            rpc_blocks.push(rpc_block);
+        }
+        rpc_blocks.reverse();
 
+        let previous_block_hash = rpc_blocks[0].header().parent_hash();
         let previous_block = provider
-            .get_block_by_number((block_number - 1).into())
+            .get_block_by_hash(previous_block_hash)
             .full()
             .await?
-            .ok_or(HostError::ExpectedBlock(block_number))
+            .ok_or(HostError::ExpectedBlockHash(previous_block_hash))
             .map(C::Primitives::into_primitive_block)?;
 
-        tracing::info!("[{}] create rpc db", block_number);
-        #[cfg(not(feature = "execution-witness"))]
-        let rpc_db = rpc_db::BasicRpcDb::new(
-            debug_provider,
-            block_number - 1,
-            previous_block.header().state_root(),
-        );
-        #[cfg(feature = "execution-witness")]
-        let rpc_db = rpc_db::ExecutionWitnessRpcDb::new(
+        tracing::info!("[{}] create rpc db", from_block);
+        let state_root = previous_block.header().state_root();
+        let use_execution_witness = match self.witness_source {
+            WitnessSource::Proofs => false,
+            WitnessSource::ExecutionWitness => {
+                if to_block != from_block {
+                    return Err(HostError::Custom(
+                        "executing a range of blocks requires eth_getProof witnesses".to_string(),
+                    ));
+                }
+
+                true
+            }
+            WitnessSource::Auto => {
+                to_block == from_block &&
+                    self.execution_witness_failures.load(Ordering::Relaxed) <
+                        MAX_EXECUTION_WITNESS_FAILURES
+            }
+        };
+
+        if use_execution_witness {
+            let rpc_db = ExecutionWitnessRpcDb::new(debug_provider, from_block - 1, state_root)
+                .await
+                .and_then(|rpc_db| {
+                    // The witness is fetched by number, so it may be from another fork. Its
+                    // parent header is required to tell.
+                    match rpc_db.ancestor_headers.get(&(from_block - 1)) {
+                        Some(header) if header.hash_slow() == previous_block_hash => Ok(rpc_db),
+                        Some(_) => Err(RpcDbError::OtherFork(from_block)),
+                        None => Err(RpcDbError::HeaderNotFound(BlockId::hash(previous_block_hash))),
+                    }
+                });
+
+            match rpc_db {
+                Ok(rpc_db) => {
+                    self.execution_witness_failures.store(0, Ordering::Relaxed);
+                    tracing::info!("[{}] create rpc db done", from_block);
+
+                    return self
+                        .execute_with_rpc_db(
+                            rpc_db,
+                            debug_provider,
+                            chain_id,
+                            rpc_blocks,
+                            genesis,
+                            custom_beneficiary,
+                            opcode_tracking,
+                        )
+                        .await;
+                }
+                Err(err) if self.witness_source == WitnessSource::ExecutionWitness => {
+                    return Err(HostError::RpcDbError(err))
+                }
+                Err(err) => {
+                    if is_method_not_found(&err) {
+                        // Don't probe again, the next blocks won't fare any better.
+                        self.execution_witness_failures
+                            .store(MAX_EXECUTION_WITNESS_FAILURES, Ordering::Relaxed);
+                    } else {
+                        self.execution_witness_failures.fetch_add(1, Ordering::Relaxed);
+                    }
+                    tracing::warn!(
+                        "[{}] debug_executionWitness failed, falling back to eth_getProof: {}",
+                        from_block,
+                        err
+                    );
+                }
+            }
+        }
+
+        let last_header = rpc_blocks.last().expect("at least one block is executed").header();
+        let mut rpc_db = BasicRpcDb::new(debug_provider, from_block - 1, state_root)
+            .with_block_hash(previous_block_hash)
+            .with_post_state_block_number(to_block)
+            .with_post_state_block_hash(last_header.hash())
+            .with_post_state_root(last_header.state_root())
+            .with_fetch_config(self.fetch_config);
+        if let Some(debug_rpc_host) = &self.debug_rpc_host {
+            rpc_db = rpc_db.with_provider_host(debug_rpc_host.clone());
+        }
+        if let Some(fallback_rpc_host) = &self.fallback_rpc_host {
+            rpc_db = rpc_db.with_fallback_provider(provider, fallback_rpc_host.clone());
+        }
+        if let Some(rpc_cache) = &self.rpc_cache {
+            rpc_db = rpc_db.with_cache(rpc_cache.clone());
+        }
+
+        tracing::info!("[{}] create rpc db done", from_block);
+
+        self.execute_with_rpc_db(
+            rpc_db,
             debug_provider,
-            block_number - 1,
-            previous_block.header().state_root(),
+            chain_id,
+            rpc_blocks,
+            genesis,
+            custom_beneficiary,
+            opcode_tracking,
         )
         .await
-        .map_err(HostError::RpcDbError)?;
-        tracing::info!("[{}] create rpc db done", block_number);
+    }
+
+    /// Executes the given blocks on top of the [RpcDb], and generates the client input from the
+    /// accessed state.
+    #[allow(clippy::too_many_arguments)]
+    async fn execute_with_rpc_db<D, P, N>(
+        &self,
+        rpc_db: D,
+        debug_provider: &P,
+        chain_id: u64,
+        rpc_blocks: Vec<N::BlockResponse>,
+        genesis: Genesis,
+        custom_beneficiary: Option<Address>,
+        opcode_tracking: bool,
+    ) -> Result<ClientExecutorInput<C::Primitives>, HostError>
+    where
+        C::Primitives: IntoPrimitives<N> + IntoInput + BlockValidator<CS>,
+        D: RpcDb<N> + DatabaseRef<Error = ProviderError> + Send + Sync,
+        P: Provider<N>,
+        N: Network,
+    {
+        let from_block =
+            rpc_blocks.first().expect("at least one block is executed").header().number();
+        let to_block = rpc_blocks.last().expect("at least one block is executed").header().number();
 
         let cache_db = CacheDB::new(&rpc_db);
 
-        let block_executor =
+        let mut block_executor =
             BasicBlockExecutor::new(self.evm_config.clone(), cache_db, Some(chain_id));
 
-        tracing::info!(
-            "executing the block with rpc db: block_number={}, transaction_count={}",
-            block_number,
-            current_block.body().transactions().len()
-        );
+        let mut current_blocks = Vec::with_capacity(rpc_blocks.len());
 
-        let block = current_block
-            .clone()
-            .try_into_recovered()
-            .map_err(|_| HostError::FailedToRecoverSenders)?;
-
-        tracing::info!("[{}] validate block", block_number);
-        C::Primitives::validate_block(&block, self.chain_spec.clone())?;
-
-        // Validate the block header.
-        tracing::info!("[{}] validate header", block_number);
-        C::Primitives::validate_header(
-            &SealedHeader::seal_slow(C::Primitives::into_consensus_header(
-                rpc_block.header().clone(),
-            )),
-            self.chain_spec.clone(),
-        )?;
-
-        tracing::info!("[{}] execute block", block_number);
-        let now = std::time::Instant::now();
-        let execution_output = block_executor.execute(&block)?;
-        tracing::info!("[{}] block execution took {:?}", block_number, now.elapsed());
-
-        // Validate the block post execution.
-        tracing::info!("[{}] validating the block post execution", block_number);
-        C::Primitives::validate_block_post_execution(
-            &block,
-            self.chain_spec.clone(),
-            &execution_output,
-            is_goat_testnet(chain_id),
-        )?;
-
-        // Accumulate the logs bloom.
-        tracing::info!("[{}] accumulating the logs bloom", block_number);
-        let mut logs_bloom = Bloom::default();
-        execution_output.result.receipts.iter().for_each(|r| {
-            logs_bloom.accrue_bloom(&r.bloom());
-        });
-
-        let state = rpc_db.state(&execution_output.state).await.map_err(HostError::RpcDbError)?;
+        for rpc_block in rpc_blocks {
+            let block_number = rpc_block.header().number();
+            let current_block = C::Primitives::into_primitive_block(rpc_block.clone());
 
-        // Verify the state root.
-        tracing::info!("[{}] verifying the state root", block_number);
-        let state_root = {
-            let mut mutated_state = state.clone();
-            mutated_state.update(&HashedPostState::from_bundle_state::<KeccakKeyHasher>(
-                &execution_output.state.state,
-            ));
-            mutated_state.state_root()
-        };
-        if state_root != current_block.header().state_root() {
-            return Err(HostError::StateRootMismatch(
-                state_root,
-                current_block.header().state_root(),
-            ));
+            tracing::info!(
+                "executing the block with rpc db: block_number={}, transaction_count={}",
+                block_number,
+                current_block.body().transactions().len()
+            );
+
+            let block = current_block
+                .clone()
+                .try_into_recovered()
+                .map_err(|_| HostError::FailedToRecoverSenders)?;
+
+            tracing::info!("[{}] validate block", block_number);
+            C::Primitives::validate_block(&block, self.chain_spec.clone())?;
+
+            // Validate the block header.
+            tracing::info!("[{}] validate header", block_number);
+            C::Primitives::validate_header(
+                &SealedHeader::seal_slow(C::Primitives::into_consensus_header(
+                    rpc_block.header().clone(),
+                )),
+                self.chain_spec.clone(),
+            )?;
+
+            if self.fetch_config.prefetch {
+                // The prestate of a block includes the changes of the previous ones, so it is
+                // prefetched only once they have been executed. The prefetch is an optimization,
+                // the accessed state is fetched lazily otherwise.
+                match rpc_db.prefetch(block_number, rpc_block.header().hash()).await {
+                    Ok(accounts) => {
+                        tracing::info!("[{}] prefetched {} accounts", block_number, accounts)
+                    }
+                    Err(err) => tracing::warn!("[{}] {}", block_number, err),
+                }
+            }
+
+            tracing::info!("[{}] execute block", block_number);
+            let now = std::time::Instant::now();
+            let execution_result = block_executor.execute_one(&block)?;
+            tracing::info!("[{}] block execution took {:?}", block_number, now.elapsed());
+
+            // Validate the block post execution.
+            tracing::info!("[{}] validating the block post execution", block_number);
+            if let Err(err) = C::Primitives::validate_block_post_execution(
+                &block,
+                self.chain_spec.clone(),
+                &execution_result,
+                is_goat_testnet(chain_id),
+            ) {
+                let receipts = execution_result
+                    .receipts
+                    .iter()
+                    .map(|receipt| (receipt.status(), receipt.cumulative_gas_used()))
+                    .collect::<Vec<_>>();
+                drop(block_executor);
+                report_receipt_divergences(
+                    debug_provider,
+                    block_number,
+                    rpc_block.header().hash(),
+                    &receipts,
+                )
+                .await;
+
+                return Err(err.into());
+            }
+
+            // Accumulate the logs bloom.
+            tracing::info!("[{}] accumulating the logs bloom", block_number);
+            let mut logs_bloom = Bloom::default();
+            execution_result.receipts.iter().for_each(|r| {
+                logs_bloom.accrue_bloom(&r.bloom());
+            });
+
+            // Derive the block header.
+            //
+            // Note: the receipts root and gas used are verified by
+            // `validate_block_post_execution`, and the state root of the last block is verified
+            // once all the blocks have been executed.
+            let header = Header {
+                parent_hash: current_block.header().parent_hash(),
+                ommers_hash: current_block.header().ommers_hash(),
+                beneficiary: current_block.header().beneficiary(),
+                state_root: current_block.header().state_root(),
+                transactions_root: current_block.header().transactions_root(),
+                receipts_root: current_block.header().receipts_root(),
+                logs_bloom,
+                difficulty: current_block.header().difficulty(),
+                number: current_block.header().number(),
+                gas_limit: current_block.header().gas_limit(),
+                gas_used: current_block.header().gas_used(),
+                timestamp: current_block.header().timestamp(),
+                extra_data: current_block.header().extra_data().clone(),
+                mix_hash: current_block.header().mix_hash().unwrap(),
+                nonce: current_block.header().nonce().unwrap(),
+                base_fee_per_gas: current_block.header().base_fee_per_gas(),
+                withdrawals_root: current_block.header().withdrawals_root(),
+                blob_gas_used: current_block.header().blob_gas_used(),
+                excess_blob_gas: current_block.header().excess_blob_gas(),
+                parent_beacon_block_root: current_block.header().parent_beacon_block_root(),
+                requests_hash: current_block.header().requests_hash(),
+            };
+
+            // Assert the derived header is correct.
+            let constructed_header_hash = header.hash_slow();
+            let target_hash = current_block.header().hash_slow();
+            if constructed_header_hash != target_hash {
+                let diff = HeaderDiff::new(
+                    &header,
+                    &C::Primitives::into_consensus_header(rpc_block.header().clone()),
+                );
+                return Err(HostError::HeaderMismatch(constructed_header_hash, target_hash, diff));
+            }
+
+            current_blocks.push(current_block);
         }
 
-        // Derive the block header.
-        //
-        // Note: the receipts root and gas used are verified by `validate_block_post_execution`.
-        let header = Header {
-            parent_hash: current_block.header().parent_hash(),
-            ommers_hash: current_block.header().ommers_hash(),
-            beneficiary: current_block.header().beneficiary(),
-            state_root,
-            transactions_root: current_block.header().transactions_root(),
-            receipts_root: current_block.header().receipts_root(),
-            logs_bloom,
-            difficulty: current_block.header().difficulty(),
-            number: current_block.header().number(),
-            gas_limit: current_block.header().gas_limit(),
-            gas_used: current_block.header().gas_used(),
-            timestamp: current_block.header().timestamp(),
-            extra_data: current_block.header().extra_data().clone(),
-            mix_hash: current_block.header().mix_hash().unwrap(),
-            nonce: current_block.header().nonce().unwrap(),
-            base_fee_per_gas: current_block.header().base_fee_per_gas(),
-            withdrawals_root: current_block.header().withdrawals_root(),
-            blob_gas_used: current_block.header().blob_gas_used(),
-            excess_blob_gas: current_block.header().excess_blob_gas(),
-            parent_beacon_block_root: current_block.header().parent_beacon_block_root(),
-            requests_hash: current_block.header().requests_hash(),
-        };
+        let bundle_state = block_executor.into_state().take_bundle();
+
+        let state = rpc_db.state(&bundle_state).await.map_err(HostError::RpcDbError)?;
+
+        // Verify the state root.
+        let last_block = current_blocks.last().expect("at least one block is executed");
+        tracing::info!("[{}] verifying the state root", to_block);
+        let mut mutated_state = state.clone();
+        mutated_state
+            .update(&HashedPostState::from_bundle_state::<KeccakKeyHasher>(&bundle_state.state));
+        let state_root = mutated_state.state_root();
+        if state_root != last_block.header().state_root() {
+            if self.diagnose_state_mismatch {
+                report_state_root_mismatch(
+                    debug_provider,
+                    to_block,
+                    last_block.header().hash_slow(),
+                    &bundle_state,
+                    &mutated_state,
+                    self.fetch_config.max_concurrent_requests,
+                )
+                .await;
+            }
+
+            return Err(HostError::StateRootMismatch(state_root, last_block.header().state_root()));
+        }
 
         let ancestor_headers = rpc_db.ancestor_headers().await.map_err(HostError::RpcDbError)?;
 
-        // Assert the derived header is correct.
-        let constructed_header_hash = header.hash_slow();
-        let target_hash = current_block.header().hash_slow();
-        if constructed_header_hash != target_hash {
-            return Err(HostError::HeaderMismatch(constructed_header_hash, target_hash));
+        // The code of the contracts created by a block is never fetched, while the subsequent
+        // blocks of the range may call them, so it is added to the fetched bytecodes.
+        let mut bytecodes = rpc_db.bytecodes();
+        if current_blocks.len() > 1 {
+            let fetched = bytecodes.iter().map(Bytecode::hash_slow).collect::<BTreeSet<_>>();
+            let created = bundle_state
+                .contracts
+                .iter()
+                .filter(|(code_hash, _)| !fetched.contains(*code_hash))
+                .collect::<BTreeMap<_, _>>();
+            bytecodes.extend(created.into_values().cloned());
         }
 
         // Log the result.
         tracing::info!(
-            "successfully executed block: block_number={}, block_hash={}, state_root={}",
-            current_block.header().number(),
-            constructed_header_hash,
+            "successfully executed blocks: from_block={}, to_block={}, block_hash={}, state_root={}",
+            from_block,
+            to_block,
+            last_block.header().hash_slow(),
             state_root
         );
 
         // Create the client input.
+        let mut blocks = current_blocks.into_iter().map(C::Primitives::into_input_block);
         let client_input = ClientExecutorInput {
-            current_block: C::Primitives::into_input_block(current_block),
+            current_block: blocks.next().expect("at least one block is executed"),
+            subsequent_blocks: blocks.collect(),
             ancestor_headers,
             parent_state: state,
-            bytecodes: rpc_db.bytecodes(),
+            bytecodes,
             genesis,
             custom_beneficiary,
             opcode_tracking,
         };
-        tracing::info!("[{}] successfully generated client input", block_number);
+        tracing::info!("[{}] successfully generated client input", from_block);
 
         Ok(client_input)
     }
 }
+
+/// Whether the RPC rejected the call because it doesn't implement the method.
+fn is_method_not_found(err: &RpcDbError) -> bool {
+    match err {
+        RpcDbError::Transport(err) => err.as_error_resp().is_some_and(|resp| resp.code == -32601),
+        _ => false,
+    }
+}
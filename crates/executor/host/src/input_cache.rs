@@ -0,0 +1,308 @@
+use std::borrow::Cow;
+
+use eyre::bail;
+use guest_executor::io::ClientExecutorInput;
+use reth_primitives_traits::NodePrimitives;
+use revm_primitives::{keccak256, B256};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+
+/// The bytes the input cache files start with.
+const INPUT_CACHE_MAGIC: [u8; 8] = *b"RPINPUT\0";
+
+/// The version of the input cache file layout. It must be bumped whenever the serialization of
+/// [ClientExecutorInput] changes, so that the inputs cached by older hosts are not loaded.
+const INPUT_CACHE_VERSION: u32 = 1;
+
+/// The zstd level the cached inputs are compressed with.
+const ZSTD_LEVEL: i32 = 3;
+
+/// The header following the magic bytes in the cache files, and preceding the serialized
+/// [ClientExecutorInput].
+#[derive(Debug, Serialize, Deserialize)]
+struct InputCacheHeader {
+    version: u32,
+    chain_id: u64,
+    from_block: u64,
+    to_block: u64,
+    /// The hash of the last block of the input.
+    block_hash: B256,
+    /// The keccak256 hash of the bincode serialized input, before compression.
+    input_hash: B256,
+    /// Whether the serialized input is compressed with zstd.
+    compressed: bool,
+}
+
+/// Returns the key of the cached client input for the given block range in the [InputStore],
+/// `block_hash` being the hash of the last block so that the inputs of different forks don't
+/// collide.
+///
+/// [InputStore]: crate::InputStore
+pub(crate) fn input_cache_key(
+    chain_id: u64,
+    from_block: u64,
+    to_block: u64,
+    block_hash: B256,
+) -> String {
+    format!("{}{block_hash}.bin", input_cache_prefix(chain_id, from_block, to_block))
+}
+
+/// Returns the prefix shared by the keys of the cached client inputs of the given block range,
+/// whatever their last block.
+pub(crate) fn input_cache_prefix(chain_id: u64, from_block: u64, to_block: u64) -> String {
+    if from_block == to_block {
+        format!("input/{chain_id}/{from_block}-")
+    } else {
+        format!("input/{chain_id}/{from_block}-{to_block}-")
+    }
+}
+
+/// Returns the hash of the last block of the cached client input with the given key, if it is
+/// the key of an input of the block range.
+pub(crate) fn parse_input_cache_key(
+    key: &str,
+    chain_id: u64,
+    from_block: u64,
+    to_block: u64,
+) -> Option<B256> {
+    key.strip_prefix(&input_cache_prefix(chain_id, from_block, to_block))?
+        .strip_suffix(".bin")?
+        .parse()
+        .ok()
+}
+
+/// Encodes the client input of a block range into a cache entry.
+pub(crate) fn encode_input<P: NodePrimitives>(
+    chain_id: u64,
+    from_block: u64,
+    to_block: u64,
+    client_input: &ClientExecutorInput<P>,
+    compress: bool,
+) -> eyre::Result<Vec<u8>>
+where
+    ClientExecutorInput<P>: Serialize,
+{
+    let serialized = bincode::serialize(client_input)?;
+    let header = InputCacheHeader {
+        version: INPUT_CACHE_VERSION,
+        chain_id,
+        from_block,
+        to_block,
+        block_hash: client_input.last_block().header.hash_slow(),
+        input_hash: keccak256(&serialized),
+        compressed: compress,
+    };
+    let payload =
+        if compress { zstd::stream::encode_all(&serialized[..], ZSTD_LEVEL)? } else { serialized };
+
+    let mut entry = INPUT_CACHE_MAGIC.to_vec();
+    bincode::serialize_into(&mut entry, &header)?;
+    entry.extend_from_slice(&payload);
+
+    Ok(entry)
+}
+
+/// Decodes a cached client input.
+///
+/// The error describes why the entry is stale or corrupted: written by another version of the
+/// host, for another chain, block range or last block than `block_hash`, or not matching its hash.
+pub(crate) fn decode_input<P: NodePrimitives + DeserializeOwned>(
+    entry: &[u8],
+    chain_id: u64,
+    from_block: u64,
+    to_block: u64,
+    block_hash: B256,
+) -> eyre::Result<ClientExecutorInput<P>> {
+    let Some(mut reader) = entry.strip_prefix(&INPUT_CACHE_MAGIC) else {
+        bail!("the entry has no header, it was written by an older version of the host");
+    };
+
+    let header: InputCacheHeader = bincode::deserialize_from(&mut reader)
+        .map_err(|err| eyre::eyre!("the header is corrupted: {err}"))?;
+
+    if header.version != INPUT_CACHE_VERSION {
+        bail!(
+            "unsupported input cache version {}, the current one is {}",
+            header.version,
+            INPUT_CACHE_VERSION
+        );
+    }
+
+    if header.chain_id != chain_id {
+        bail!("the entry is for chain {}, not {}", header.chain_id, chain_id);
+    }
+
+    if (header.from_block, header.to_block) != (from_block, to_block) {
+        bail!(
+            "the entry is for blocks {}-{}, not {}-{}",
+            header.from_block,
+            header.to_block,
+            from_block,
+            to_block
+        );
+    }
+
+    if header.block_hash != block_hash {
+        bail!("the entry is for block {}, not {}", header.block_hash, block_hash);
+    }
+
+    // The reader is left at the start of the payload.
+    let serialized = if header.compressed {
+        Cow::Owned(
+            zstd::stream::decode_all(reader)
+                .map_err(|err| eyre::eyre!("the compressed input is corrupted: {err}"))?,
+        )
+    } else {
+        Cow::Borrowed(reader)
+    };
+
+    let input_hash = keccak256(&serialized);
+    if input_hash != header.input_hash {
+        bail!("the input hash {} does not match the expected {}", input_hash, header.input_hash);
+    }
+
+    let client_input: ClientExecutorInput<P> = bincode::deserialize(&serialized)?;
+    let input_block_hash = client_input.last_block().header.hash_slow();
+    if input_block_hash != block_hash {
+        bail!("the input is for block {}, not {}", input_block_hash, block_hash);
+    }
+
+    Ok(client_input)
+}
+
+#[cfg(test)]
+mod tests {
+    use alloy_consensus::{Block, BlockBody, Header};
+    use alloy_primitives::map::HashMap;
+    use mpt::EthereumState;
+    use primitives::genesis::Genesis;
+    use reth_ethereum_primitives::EthPrimitives;
+
+    use super::*;
+
+    const CHAIN_ID: u64 = 1;
+
+    fn block(number: u64) -> Block<reth_ethereum_primitives::TransactionSigned> {
+        Block {
+            header: Header { number, timestamp: 1_700_000_000 + number, ..Default::default() },
+            body: BlockBody { transactions: vec![], ommers: vec![], withdrawals: None },
+        }
+    }
+
+    fn client_input() -> ClientExecutorInput<EthPrimitives> {
+        let parent = Header { number: 41, ..Default::default() };
+        ClientExecutorInput {
+            current_block: block(42),
+            subsequent_blocks: vec![block(43)],
+            parent_state: EthereumState::from_proofs(parent.state_root, &HashMap::default())
+                .unwrap(),
+            ancestor_headers: vec![parent],
+            bytecodes: vec![],
+            genesis: Genesis::Mainnet,
+            custom_beneficiary: None,
+            opcode_tracking: false,
+        }
+    }
+
+    /// Rewrites the header of an entry, keeping its payload.
+    fn rewrite_header(entry: &[u8], f: impl FnOnce(&mut InputCacheHeader)) -> Vec<u8> {
+        let mut reader = &entry[INPUT_CACHE_MAGIC.len()..];
+        let mut header: InputCacheHeader = bincode::deserialize_from(&mut reader).unwrap();
+        f(&mut header);
+
+        let mut rewritten = INPUT_CACHE_MAGIC.to_vec();
+        bincode::serialize_into(&mut rewritten, &header).unwrap();
+        rewritten.extend_from_slice(reader);
+        rewritten
+    }
+
+    fn decode(entry: &[u8], block_hash: B256) -> eyre::Result<ClientExecutorInput<EthPrimitives>> {
+        decode_input(entry, CHAIN_ID, 42, 43, block_hash)
+    }
+
+    #[test]
+    fn test_parse_key() {
+        let block_hash = B256::repeat_byte(1);
+
+        let key = input_cache_key(CHAIN_ID, 42, 42, block_hash);
+        assert_eq!(parse_input_cache_key(&key, CHAIN_ID, 42, 42), Some(block_hash));
+        assert_eq!(parse_input_cache_key(&key, CHAIN_ID, 42, 43), None);
+
+        // The keys of the ranges starting with the block share its prefix.
+        let key = input_cache_key(CHAIN_ID, 42, 43, block_hash);
+        assert_eq!(parse_input_cache_key(&key, CHAIN_ID, 42, 43), Some(block_hash));
+        assert_eq!(parse_input_cache_key(&key, CHAIN_ID, 42, 42), None);
+    }
+
+    #[test]
+    fn test_roundtrip() {
+        let input = client_input();
+        let block_hash = input.last_block().header.hash_slow();
+
+        for compress in [false, true] {
+            let entry = encode_input(CHAIN_ID, 42, 43, &input, compress).unwrap();
+            assert_eq!(decode(&entry, block_hash).unwrap(), input);
+        }
+    }
+
+    #[test]
+    fn test_wrong_magic_is_rejected() {
+        let input = client_input();
+        let block_hash = input.last_block().header.hash_slow();
+        let mut entry = encode_input(CHAIN_ID, 42, 43, &input, false).unwrap();
+        entry[0] ^= 1;
+
+        let err = decode(&entry, block_hash).unwrap_err();
+        assert!(err.to_string().contains("no header"), "{err}");
+    }
+
+    #[test]
+    fn test_wrong_version_is_rejected() {
+        let input = client_input();
+        let block_hash = input.last_block().header.hash_slow();
+        let entry = encode_input(CHAIN_ID, 42, 43, &input, false).unwrap();
+        let entry = rewrite_header(&entry, |header| header.version += 1);
+
+        let err = decode(&entry, block_hash).unwrap_err();
+        assert!(err.to_string().contains("unsupported input cache version"), "{err}");
+    }
+
+    #[test]
+    fn test_wrong_chain_is_rejected() {
+        let input = client_input();
+        let block_hash = input.last_block().header.hash_slow();
+        let entry = encode_input(CHAIN_ID + 1, 42, 43, &input, false).unwrap();
+
+        let err = decode(&entry, block_hash).unwrap_err();
+        assert!(err.to_string().contains("is for chain 2, not 1"), "{err}");
+    }
+
+    #[test]
+    fn test_wrong_range_is_rejected() {
+        let input = client_input();
+        let block_hash = input.last_block().header.hash_slow();
+        let entry = encode_input(CHAIN_ID, 41, 43, &input, false).unwrap();
+
+        let err = decode(&entry, block_hash).unwrap_err();
+        assert!(err.to_string().contains("is for blocks 41-43, not 42-43"), "{err}");
+    }
+
+    #[test]
+    fn test_wrong_block_hash_is_rejected() {
+        let input = client_input();
+        let entry = encode_input(CHAIN_ID, 42, 43, &input, false).unwrap();
+
+        let err = decode(&entry, B256::repeat_byte(1)).unwrap_err();
+        assert!(err.to_string().contains("is for block"), "{err}");
+    }
+
+    #[test]
+    fn test_corrupted_payload_is_rejected() {
+        let input = client_input();
+        let block_hash = input.last_block().header.hash_slow();
+        let mut entry = encode_input(CHAIN_ID, 42, 43, &input, false).unwrap();
+        *entry.last_mut().unwrap() ^= 1;
+
+        let err = decode(&entry, block_hash).unwrap_err();
+        assert!(err.to_string().contains("does not match"), "{err}");
+    }
+}
@@ -0,0 +1,357 @@
+use std::{
+    fmt::Debug,
+    fs::File,
+    io::{ErrorKind, Write},
+    path::{Path, PathBuf},
+    time::SystemTime,
+};
+
+use async_trait::async_trait;
+use tokio::task;
+use tracing::{debug, warn};
+
+use crate::utils::write_file;
+
+/// A store of the generated client inputs, keyed by their path relative to the store root, e.g.
+/// `input/{chain_id}/{block_number}-{block_hash}.bin`.
+#[async_trait]
+pub trait InputStore: Debug + Send + Sync {
+    /// Returns the entry with the given key, if any.
+    async fn get(&self, key: &str) -> eyre::Result<Option<Vec<u8>>>;
+
+    /// Inserts or replaces the entry with the given key.
+    async fn put(&self, key: &str, data: Vec<u8>) -> eyre::Result<()>;
+
+    /// Removes the entry with the given key, if any.
+    async fn delete(&self, key: &str) -> eyre::Result<()>;
+
+    /// Returns the sorted keys of the entries starting with `prefix`, e.g. `input/1/42-`. Only the
+    /// entries in the directory of the prefix are listed, not the ones of its subdirectories.
+    async fn list(&self, prefix: &str) -> eyre::Result<Vec<String>>;
+}
+
+/// An [InputStore] keeping the entries in a local directory.
+///
+/// When a maximum size is set, the least recently used entries are evicted after each insertion
+/// until the input files fit in it.
+#[derive(Debug, Clone)]
+pub struct FsInputStore {
+    dir: PathBuf,
+    max_size: Option<u64>,
+}
+
+impl FsInputStore {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into(), max_size: None }
+    }
+
+    /// Sets the maximum total size of the entries, in bytes.
+    pub fn with_max_size(mut self, max_size: Option<u64>) -> Self {
+        self.max_size = max_size;
+        self
+    }
+
+    fn path(&self, key: &str) -> PathBuf {
+        self.dir.join(key)
+    }
+}
+
+#[async_trait]
+impl InputStore for FsInputStore {
+    async fn get(&self, key: &str) -> eyre::Result<Option<Vec<u8>>> {
+        let path = self.path(key);
+        task::spawn_blocking(move || {
+            let data = match std::fs::read(&path) {
+                Ok(data) => data,
+                Err(err) if err.kind() == ErrorKind::NotFound => return Ok(None),
+                Err(err) => return Err(err.into()),
+            };
+
+            // The modification time tracks the last use of the entries, for the eviction. The
+            // store may be read-only, e.g. a mounted copy of the cache, in which case the entry
+            // is still returned.
+            let touched = File::options()
+                .write(true)
+                .open(&path)
+                .and_then(|file| file.set_modified(SystemTime::now()));
+            if let Err(err) = touched {
+                debug!("Failed to update the modification time of {}: {}", path.display(), err);
+            }
+
+            Ok(Some(data))
+        })
+        .await?
+    }
+
+    async fn put(&self, key: &str, data: Vec<u8>) -> eyre::Result<()> {
+        let path = self.path(key);
+        let dir = self.dir.clone();
+        let max_size = self.max_size;
+        task::spawn_blocking(move || {
+            write_file(&path, |writer| Ok(writer.write_all(&data)?))?;
+
+            if let Some(max_size) = max_size {
+                evict(&dir, max_size, &path)?;
+            }
+
+            Ok(())
+        })
+        .await?
+    }
+
+    async fn delete(&self, key: &str) -> eyre::Result<()> {
+        let path = self.path(key);
+        task::spawn_blocking(move || match std::fs::remove_file(path) {
+            Err(err) if err.kind() != ErrorKind::NotFound => Err(err.into()),
+            _ => Ok(()),
+        })
+        .await?
+    }
+
+    async fn list(&self, prefix: &str) -> eyre::Result<Vec<String>> {
+        let (parent, name_prefix) = match prefix.rsplit_once('/') {
+            Some((parent, name_prefix)) => (format!("{parent}/"), name_prefix.to_string()),
+            None => (String::new(), prefix.to_string()),
+        };
+        let dir = self.dir.join(&parent);
+        task::spawn_blocking(move || {
+            let entries = match std::fs::read_dir(dir) {
+                Ok(entries) => entries,
+                Err(err) if err.kind() == ErrorKind::NotFound => return Ok(vec![]),
+                Err(err) => return Err(err.into()),
+            };
+
+            let mut keys = Vec::new();
+            for entry in entries {
+                let entry = entry?;
+                let Some(name) = entry.file_name().to_str().map(str::to_string) else {
+                    continue;
+                };
+                if entry.file_type()?.is_file() && name.starts_with(&name_prefix) {
+                    keys.push(format!("{parent}{name}"));
+                }
+            }
+            keys.sort_unstable();
+
+            Ok(keys)
+        })
+        .await?
+    }
+}
+
+/// Removes the least recently used input files (`input/**/*.bin`) of the store at `dir` until
+/// their total size is at most `max_size`, keeping the `keep` file.
+///
+/// The other files of the directory, e.g. the proving keys when the key cache shares it, are
+/// neither counted nor removed.
+fn evict(dir: &Path, max_size: u64, keep: &Path) -> std::io::Result<()> {
+    let input_dir = dir.join("input");
+    let mut files = Vec::new();
+    let mut dirs = vec![input_dir.clone()];
+    while let Some(dir) = dirs.pop() {
+        let entries = match std::fs::read_dir(dir) {
+            Ok(entries) => entries,
+            Err(err) if err.kind() == ErrorKind::NotFound => continue,
+            Err(err) => return Err(err),
+        };
+
+        for entry in entries {
+            let entry = entry?;
+            let metadata = entry.metadata()?;
+            let path = entry.path();
+            if metadata.is_dir() {
+                dirs.push(path);
+            } else if path.extension().is_some_and(|extension| extension == "bin") {
+                files.push((metadata.modified()?, metadata.len(), path));
+            }
+        }
+    }
+
+    let mut size = files.iter().map(|(_, len, _)| len).sum::<u64>();
+    files.sort_unstable();
+    for (_, len, path) in files {
+        if size <= max_size {
+            break;
+        }
+
+        if path != keep {
+            debug!("Evicting {} from the input store", path.display());
+            std::fs::remove_file(&path)?;
+            size -= len;
+        }
+    }
+
+    if size > max_size {
+        warn!(
+            "The inputs of the store {} exceed its maximum size of {} bytes",
+            dir.display(),
+            max_size
+        );
+    }
+
+    Ok(())
+}
+
+/// An [InputStore] keeping the entries in an S3-compatible object storage, e.g. AWS S3 or MinIO.
+#[cfg(feature = "s3")]
+#[derive(Debug)]
+pub struct S3InputStore {
+    store: object_store::aws::AmazonS3,
+    prefix: object_store::path::Path,
+}
+
+#[cfg(feature = "s3")]
+impl S3InputStore {
+    /// Creates a store from a `s3://<bucket>/<prefix>` URL.
+    ///
+    /// The credentials, region and endpoint are read from the standard `AWS_*` env vars, e.g.
+    /// `AWS_ENDPOINT=http://localhost:9000` and `AWS_ALLOW_HTTP=true` for a local MinIO.
+    pub fn from_url(url: &url::Url) -> eyre::Result<Self> {
+        let store =
+            object_store::aws::AmazonS3Builder::from_env().with_url(url.as_str()).build()?;
+        let prefix = object_store::path::Path::from(url.path().trim_start_matches('/'));
+
+        Ok(Self { store, prefix })
+    }
+
+    fn path(&self, key: &str) -> object_store::path::Path {
+        key.split('/').fold(self.prefix.clone(), |path, part| path.child(part))
+    }
+}
+
+#[cfg(feature = "s3")]
+#[async_trait]
+impl InputStore for S3InputStore {
+    async fn get(&self, key: &str) -> eyre::Result<Option<Vec<u8>>> {
+        use object_store::ObjectStore;
+
+        match self.store.get(&self.path(key)).await {
+            Ok(result) => Ok(Some(result.bytes().await?.to_vec())),
+            Err(object_store::Error::NotFound { .. }) => Ok(None),
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    async fn put(&self, key: &str, data: Vec<u8>) -> eyre::Result<()> {
+        use object_store::ObjectStore;
+
+        self.store.put(&self.path(key), data.into()).await?;
+
+        Ok(())
+    }
+
+    async fn delete(&self, key: &str) -> eyre::Result<()> {
+        use object_store::ObjectStore;
+
+        match self.store.delete(&self.path(key)).await {
+            Ok(()) | Err(object_store::Error::NotFound { .. }) => Ok(()),
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    async fn list(&self, prefix: &str) -> eyre::Result<Vec<String>> {
+        use object_store::ObjectStore;
+
+        let dir = match prefix.rsplit_once('/') {
+            Some((parent, _)) => self.path(parent),
+            None => self.prefix.clone(),
+        };
+        let result = self.store.list_with_delimiter(Some(&dir)).await?;
+
+        let mut keys = result
+            .objects
+            .into_iter()
+            .filter_map(|object| {
+                let key = object
+                    .location
+                    .prefix_match(&self.prefix)?
+                    .map(|part| part.as_ref().to_string())
+                    .collect::<Vec<_>>()
+                    .join("/");
+                key.starts_with(prefix).then_some(key)
+            })
+            .collect::<Vec<_>>();
+        keys.sort_unstable();
+
+        Ok(keys)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_evict() {
+        let dir = std::env::temp_dir().join(format!("input-store-{}", std::process::id()));
+        std::fs::create_dir_all(dir.join("input/1")).unwrap();
+
+        let base = SystemTime::now();
+        for (i, name) in ["1.bin", "2.bin", "3.bin"].into_iter().enumerate() {
+            let path = dir.join("input/1").join(name);
+            std::fs::write(&path, [0u8; 10]).unwrap();
+            File::options()
+                .write(true)
+                .open(&path)
+                .unwrap()
+                .set_modified(base - std::time::Duration::from_secs(10 - i as u64))
+                .unwrap();
+        }
+
+        // The oldest entry is kept when it is the one just inserted.
+        evict(&dir, 20, &dir.join("input/1/1.bin")).unwrap();
+        assert!(dir.join("input/1/1.bin").exists());
+        assert!(!dir.join("input/1/2.bin").exists());
+        assert!(dir.join("input/1/3.bin").exists());
+
+        evict(&dir, 10, &dir.join("input/1/3.bin")).unwrap();
+        assert!(!dir.join("input/1/1.bin").exists());
+        assert!(dir.join("input/1/3.bin").exists());
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_evict_keeps_other_files() {
+        let dir = std::env::temp_dir().join(format!("input-store-other-{}", std::process::id()));
+        std::fs::create_dir_all(dir.join("input/1")).unwrap();
+
+        // Older than the inputs, and bigger than the maximum size on their own.
+        for path in [dir.join("keys.bin"), dir.join("input/1/2.tmp")] {
+            std::fs::write(&path, [0u8; 100]).unwrap();
+            File::options()
+                .write(true)
+                .open(&path)
+                .unwrap()
+                .set_modified(SystemTime::now() - std::time::Duration::from_secs(60))
+                .unwrap();
+        }
+        std::fs::write(dir.join("input/1/1.bin"), [0u8; 10]).unwrap();
+
+        evict(&dir, 10, &dir.join("input/1/1.bin")).unwrap();
+        assert!(dir.join("keys.bin").exists());
+        assert!(dir.join("input/1/2.tmp").exists());
+        assert!(dir.join("input/1/1.bin").exists());
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_list() {
+        let dir = std::env::temp_dir().join(format!("input-store-list-{}", std::process::id()));
+        let store = FsInputStore::new(&dir);
+        for key in
+            ["input/1/42-a.bin", "input/1/42-43-b.bin", "input/1/43-c.bin", "input/2/42-d.bin"]
+        {
+            store.put(key, vec![0]).await.unwrap();
+        }
+
+        assert_eq!(
+            store.list("input/1/42-").await.unwrap(),
+            vec!["input/1/42-43-b.bin".to_string(), "input/1/42-a.bin".to_string()]
+        );
+        assert!(store.list("input/3/42-").await.unwrap().is_empty());
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,199 @@
+use std::{fs::File, io::BufReader, path::Path, sync::Arc};
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use tokio::task;
+use tracing::{info, warn};
+use zkm_prover::components::DefaultProverComponents;
+use zkm_sdk::{HashableKey, Prover, ZKMProvingKey, ZKMVerifyingKey};
+
+use crate::utils::write_file;
+
+/// The version of the key cache file layout.
+const KEY_CACHE_VERSION: u8 = 2;
+
+/// An entry of the key cache, holding the serialized proving and verifying keys of a program.
+///
+/// The checksum of the serialized keys is stored alongside them, so that an entry corrupted on
+/// disk is detected before being deserialized.
+#[derive(Serialize, Deserialize)]
+struct KeyCacheEntry {
+    version: u8,
+    elf_id: String,
+    checksum: [u8; 32],
+    keys: Vec<u8>,
+}
+
+/// Returns the identifier of the ELF, i.e. its hex encoded SHA-256 digest.
+pub(crate) fn elf_id(elf: &[u8]) -> String {
+    hex::encode(Sha256::digest(elf))
+}
+
+/// Sets up the proving and verifying keys of the ELF.
+///
+/// When a key cache directory is provided, the keys are loaded from it if they were already
+/// generated for the same ELF, and saved to it otherwise.
+pub(crate) async fn setup_keys<P>(
+    client: Arc<P>,
+    elf: Vec<u8>,
+    key_cache_dir: Option<&Path>,
+) -> eyre::Result<(ZKMProvingKey, ZKMVerifyingKey)>
+where
+    P: Prover<DefaultProverComponents> + 'static,
+{
+    let elf_id = elf_id(&elf);
+    let cache_path = key_cache_dir.map(|dir| dir.join(format!("{elf_id}.bin")));
+
+    if let Some(cache_path) = &cache_path {
+        if cache_path.exists() {
+            match load_keys(cache_path, &elf_id, &elf) {
+                Ok(keys) => {
+                    info!("Loaded the keys of ELF {} from {}", elf_id, cache_path.display());
+                    return Ok(keys);
+                }
+                Err(err) => {
+                    warn!("Failed to load the cached keys of ELF {}, regenerating: {}", elf_id, err)
+                }
+            }
+        }
+    }
+
+    // Setup the proving key and verification key.
+    let (pk, vk) = task::spawn_blocking(move || {
+        let (pk, vk) = client.setup(&elf);
+        (pk, vk)
+    })
+    .await?;
+
+    if let Some(cache_path) = cache_path {
+        if let Err(err) = save_keys(&cache_path, &elf_id, &pk, &vk) {
+            warn!("Failed to cache the keys to {}: {}", cache_path.display(), err);
+        }
+    }
+
+    Ok((pk, vk))
+}
+
+/// Loads the keys of the ELF from the cache, checking that they were generated for it and that
+/// the verifying key is the one of the proving key.
+fn load_keys(
+    cache_path: &Path,
+    elf_id: &str,
+    elf: &[u8],
+) -> eyre::Result<(ZKMProvingKey, ZKMVerifyingKey)> {
+    let keys = read_entry(cache_path, elf_id)?;
+    let (pk, vk): (ZKMProvingKey, ZKMVerifyingKey) = bincode::deserialize(&keys)?;
+
+    if pk.elf[..] != *elf {
+        eyre::bail!("the cached proving key was generated for another ELF");
+    }
+
+    if pk.vk.bytes32() != vk.bytes32() {
+        eyre::bail!("the cached verifying key doesn't match the proving key");
+    }
+
+    Ok((pk, vk))
+}
+
+fn save_keys(
+    cache_path: &Path,
+    elf_id: &str,
+    pk: &ZKMProvingKey,
+    vk: &ZKMVerifyingKey,
+) -> eyre::Result<()> {
+    write_entry(cache_path, elf_id, bincode::serialize(&(pk, vk))?)
+}
+
+/// Reads the serialized keys of a cache entry, checking its version, ELF and checksum.
+fn read_entry(cache_path: &Path, elf_id: &str) -> eyre::Result<Vec<u8>> {
+    let reader = BufReader::new(File::open(cache_path)?);
+    let entry: KeyCacheEntry = bincode::deserialize_from(reader)?;
+
+    if entry.version != KEY_CACHE_VERSION {
+        eyre::bail!("unsupported key cache version {}", entry.version);
+    }
+
+    if entry.elf_id != elf_id {
+        eyre::bail!("the cached keys were generated for another ELF");
+    }
+
+    if <[u8; 32]>::from(Sha256::digest(&entry.keys)) != entry.checksum {
+        eyre::bail!("the cached keys are corrupted");
+    }
+
+    Ok(entry.keys)
+}
+
+fn write_entry(cache_path: &Path, elf_id: &str, keys: Vec<u8>) -> eyre::Result<()> {
+    let entry = KeyCacheEntry {
+        version: KEY_CACHE_VERSION,
+        elf_id: elf_id.to_string(),
+        checksum: Sha256::digest(&keys).into(),
+        keys,
+    };
+
+    write_file(cache_path, |writer| Ok(bincode::serialize_into(writer, &entry)?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cache_dir(name: &str) -> std::path::PathBuf {
+        let dir = std::env::temp_dir().join(format!("key-cache-{name}-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn test_entry_roundtrip() {
+        let dir = cache_dir("roundtrip");
+        let path = dir.join("keys.bin");
+
+        write_entry(&path, "elf", vec![1, 2, 3]).unwrap();
+        assert_eq!(read_entry(&path, "elf").unwrap(), vec![1, 2, 3]);
+
+        // No temporary file is left behind.
+        assert_eq!(std::fs::read_dir(&dir).unwrap().count(), 1);
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_stale_entry_is_rejected() {
+        let dir = cache_dir("stale");
+        let path = dir.join("keys.bin");
+
+        write_entry(&path, "elf", vec![1, 2, 3]).unwrap();
+        assert!(read_entry(&path, "other-elf").is_err());
+
+        let entry = KeyCacheEntry {
+            version: KEY_CACHE_VERSION - 1,
+            elf_id: "elf".to_string(),
+            checksum: Sha256::digest([1, 2, 3]).into(),
+            keys: vec![1, 2, 3],
+        };
+        std::fs::write(&path, bincode::serialize(&entry).unwrap()).unwrap();
+        assert!(read_entry(&path, "elf").is_err());
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_corrupted_entry_is_rejected() {
+        let dir = cache_dir("corrupted");
+        let path = dir.join("keys.bin");
+
+        write_entry(&path, "elf", vec![1, 2, 3]).unwrap();
+        let mut bytes = std::fs::read(&path).unwrap();
+        *bytes.last_mut().unwrap() ^= 1;
+        std::fs::write(&path, &bytes).unwrap();
+        assert!(read_entry(&path, "elf").is_err());
+
+        // A truncated entry is rejected as well.
+        std::fs::write(&path, &bytes[..bytes.len() / 2]).unwrap();
+        assert!(read_entry(&path, "elf").is_err());
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -4,15 +4,20 @@ use alloy_chains::Chain;
 pub use error::Error as HostError;
 use guest_executor::custom::CustomEvmFactory;
 use primitives::genesis::Genesis;
+pub use provider::RetryConfig;
 use reth_chainspec::ChainSpec;
 use reth_evm_ethereum::EthEvmConfig;
 use reth_optimism_chainspec::OpChainSpec;
 use reth_optimism_evm::OpEvmConfig;
 use revm_primitives::Address;
+pub use rpc_db::{FetchConfig, RpcCache, DEFAULT_MAX_CONCURRENT_REQUESTS, DEFAULT_RPC_CACHE_SIZE};
 use std::{path::PathBuf, sync::Arc};
 use url::Url;
 use zkm_sdk::ZKMProofKind;
 
+mod aggregator;
+pub use aggregator::Aggregator;
+
 #[cfg(feature = "alerting")]
 pub mod alerting;
 
@@ -23,6 +28,9 @@ mod error;
 mod executor_components;
 pub use executor_components::{EthExecutorComponents, ExecutorComponents, OpExecutorComponents};
 
+mod fetch_args;
+pub use fetch_args::FetchArgs;
+
 mod full_executor;
 pub use full_executor::{build_executor, BlockExecutor, EitherExecutor, FullExecutor};
 
@@ -32,8 +40,40 @@ pub use hooks::ExecutionHooks;
 mod host_executor;
 pub use host_executor::{EthHostExecutor, HostExecutor, OpHostExecutor};
 
+mod input_cache;
+
+mod input_store;
+#[cfg(feature = "s3")]
+pub use input_store::S3InputStore;
+pub use input_store::{FsInputStore, InputStore};
+
+mod keys;
+
+mod mismatch;
+pub use mismatch::{HeaderDiff, HeaderFieldDiff};
+
+mod native_executor;
+pub use native_executor::NativeExecutor;
+
+mod proof_artifact;
+pub use proof_artifact::{ProofArtifact, ProofProgram};
+
+mod proof_kind;
+pub use proof_kind::ProofKind;
+
+mod public_values;
+pub use public_values::{
+    decode_aggregation_public_values, decode_public_values, decode_public_values_bytes,
+    expected_chain_config_hash, verify_chain_config, verify_chain_config_hash,
+};
+
+mod state_diagnostics;
+
 mod utils;
 
+mod witness_source;
+pub use witness_source::WitnessSource;
+
 pub fn create_eth_block_execution_strategy_factory(
     genesis: &Genesis,
     custom_beneficiary: Option<Address>,
@@ -54,8 +94,26 @@ pub struct Config {
     pub chain: Chain,
     pub genesis: Genesis,
     pub rpc_url: Option<Url>,
+    /// The RPCs failed over to, in order, when `rpc_url` fails.
+    pub fallback_rpc_urls: Vec<Url>,
     pub debug_rpc_url: Option<Url>,
+    pub retry_config: RetryConfig,
     pub cache_dir: Option<PathBuf>,
+    /// The maximum size of `cache_dir` in bytes, beyond which the least recently used inputs are
+    /// evicted.
+    pub cache_max_size: Option<u64>,
+    /// The `s3://<bucket>/<prefix>` URL of the object storage the client inputs are cached in,
+    /// instead of `cache_dir`.
+    pub input_store_url: Option<Url>,
+    /// Whether to compress the cached client inputs with zstd.
+    pub compress_input_cache: bool,
+    pub key_cache_dir: Option<PathBuf>,
+    pub fetch_config: FetchConfig,
+    /// The maximum number of headers, bytecodes and proofs cached across blocks, 0 to disable
+    /// the cache.
+    pub rpc_cache_size: usize,
+    pub witness_source: WitnessSource,
+    pub diagnose_state_mismatch: bool,
     pub custom_beneficiary: Option<Address>,
     pub prove_mode: Option<ZKMProofKind>,
     pub opcode_tracking: bool,
@@ -67,11 +125,39 @@ impl Config {
             chain: Chain::mainnet(),
             genesis: Genesis::Mainnet,
             rpc_url: None,
+            fallback_rpc_urls: vec![],
             debug_rpc_url: None,
+            retry_config: RetryConfig::default(),
             cache_dir: None,
+            cache_max_size: None,
+            input_store_url: None,
+            compress_input_cache: false,
+            key_cache_dir: None,
+            fetch_config: FetchConfig::default(),
+            rpc_cache_size: DEFAULT_RPC_CACHE_SIZE,
+            witness_source: WitnessSource::default(),
+            diagnose_state_mismatch: false,
             custom_beneficiary: None,
             prove_mode: None,
             opcode_tracking: false,
         }
     }
+
+    /// Returns the store the client inputs are cached in, if any.
+    pub fn input_store(&self) -> eyre::Result<Option<Arc<dyn InputStore>>> {
+        match &self.input_store_url {
+            Some(url) if url.scheme() == "s3" => {
+                #[cfg(feature = "s3")]
+                return Ok(Some(Arc::new(S3InputStore::from_url(url)?)));
+
+                #[cfg(not(feature = "s3"))]
+                eyre::bail!("The host must be built with the `s3` feature to use an S3 input store")
+            }
+            Some(url) => eyre::bail!("Unsupported input store URL scheme: {}", url.scheme()),
+            None => Ok(self.cache_dir.as_ref().map(|cache_dir| {
+                Arc::new(FsInputStore::new(cache_dir).with_max_size(self.cache_max_size))
+                    as Arc<dyn InputStore>
+            })),
+        }
+    }
 }
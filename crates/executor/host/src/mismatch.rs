@@ -0,0 +1,174 @@
+use std::fmt;
+
+use alloy_consensus::Header;
+use alloy_eips::BlockId;
+use alloy_network::ReceiptResponse;
+use alloy_primitives::B256;
+use alloy_provider::{Network, Provider};
+use tracing::{error, warn};
+
+/// A field differing between a reconstructed header and the expected one.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HeaderFieldDiff {
+    pub field: &'static str,
+    pub found: String,
+    pub expected: String,
+}
+
+/// The fields differing between a reconstructed header and the expected one.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct HeaderDiff(pub Vec<HeaderFieldDiff>);
+
+impl HeaderDiff {
+    /// Compares the `found` header against the `expected` one, field by field.
+    pub fn new(found: &Header, expected: &Header) -> Self {
+        let mut fields = Vec::new();
+
+        macro_rules! diff {
+            ($($field:ident),* $(,)?) => {
+                $(
+                    if found.$field != expected.$field {
+                        fields.push(HeaderFieldDiff {
+                            field: stringify!($field),
+                            found: format!("{:?}", found.$field),
+                            expected: format!("{:?}", expected.$field),
+                        });
+                    }
+                )*
+            };
+        }
+
+        diff!(
+            parent_hash,
+            ommers_hash,
+            beneficiary,
+            state_root,
+            transactions_root,
+            receipts_root,
+            logs_bloom,
+            difficulty,
+            number,
+            gas_limit,
+            gas_used,
+            timestamp,
+            extra_data,
+            mix_hash,
+            nonce,
+            base_fee_per_gas,
+            withdrawals_root,
+            blob_gas_used,
+            excess_blob_gas,
+            parent_beacon_block_root,
+            requests_hash,
+        );
+
+        Self(fields)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+impl fmt::Display for HeaderDiff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_empty() {
+            return write!(f, "no field differs");
+        }
+
+        for (i, diff) in self.0.iter().enumerate() {
+            if i > 0 {
+                writeln!(f)?;
+            }
+            write!(f, "{}: found {}, expected {}", diff.field, diff.found, diff.expected)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Logs the transactions whose status or gas used differ from the receipts reported by the RPC,
+/// given the computed `(status, cumulative_gas_used)` of each receipt of the block.
+///
+/// The receipts are fetched by the hash of the block, so that they are from the executed fork.
+pub(crate) async fn report_receipt_divergences<P, N>(
+    provider: &P,
+    block_number: u64,
+    block_hash: B256,
+    computed: &[(bool, u64)],
+) where
+    P: Provider<N>,
+    N: Network,
+{
+    let receipts = match provider.get_block_receipts(BlockId::hash(block_hash)).await {
+        Ok(Some(receipts)) => receipts,
+        Ok(None) => {
+            warn!("[{}] the RPC has no receipts for the block", block_number);
+            return;
+        }
+        Err(err) => {
+            warn!("[{}] failed to fetch the receipts: {}", block_number, err);
+            return;
+        }
+    };
+
+    if computed.len() != receipts.len() {
+        error!(
+            "[{}] {} receipts computed, {} expected",
+            block_number,
+            computed.len(),
+            receipts.len()
+        );
+    }
+
+    let mut previous = (0, 0);
+    for (index, (&(status, cumulative_gas_used), receipt)) in
+        computed.iter().zip(&receipts).enumerate()
+    {
+        let gas_used = cumulative_gas_used.saturating_sub(previous.0);
+        let expected_gas_used = receipt.cumulative_gas_used().saturating_sub(previous.1);
+        previous = (cumulative_gas_used, receipt.cumulative_gas_used());
+
+        if status != receipt.status() || gas_used != expected_gas_used {
+            error!(
+                "[{}] transaction {} ({}) diverges: status {} (expected {}), gas used {} (expected {}), cumulative gas used {} (expected {})",
+                block_number,
+                index,
+                receipt.transaction_hash(),
+                status,
+                receipt.status(),
+                gas_used,
+                expected_gas_used,
+                cumulative_gas_used,
+                receipt.cumulative_gas_used()
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloy_primitives::{Bloom, B256};
+
+    use super::*;
+
+    #[test]
+    fn test_header_diff() {
+        let expected = Header { number: 1, gas_used: 21_000, ..Default::default() };
+        assert!(HeaderDiff::new(&expected, &expected).is_empty());
+
+        let found = Header {
+            gas_used: 42_000,
+            logs_bloom: Bloom::repeat_byte(1),
+            requests_hash: Some(B256::ZERO),
+            ..expected.clone()
+        };
+        let diff = HeaderDiff::new(&found, &expected);
+
+        assert_eq!(
+            diff.0.iter().map(|diff| diff.field).collect::<Vec<_>>(),
+            ["logs_bloom", "gas_used", "requests_hash"]
+        );
+        assert_eq!(diff.0[1].found, "42000");
+    }
+}
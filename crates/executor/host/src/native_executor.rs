@@ -0,0 +1,187 @@
+use std::{
+    fmt::{Debug, Formatter},
+    num::NonZeroUsize,
+    sync::Arc,
+    time::Instant,
+};
+
+use crate::{
+    expected_chain_config_hash,
+    full_executor::{load_cached_input, load_or_generate_input},
+    Config, ExecutorComponents, HeaderDiff, HostError, HostExecutor, InputStore, RpcCache,
+};
+use alloy_provider::Provider;
+use eyre::bail;
+use guest_executor::{
+    executor::ClientExecutor,
+    io::{BlockPublicValues, ClientExecutorInput},
+};
+use revm_primitives::B256;
+use tokio::task;
+use tracing::{info, info_span};
+
+/// An executor running the client executor of the guest program directly on the host, without
+/// setting up the zkVM, to quickly validate the client inputs before proving them.
+///
+/// The inputs are loaded from the input store, or generated from the RPC when not cached.
+pub struct NativeExecutor<C, P>
+where
+    C: ExecutorComponents,
+    P: Provider<C::Network> + Clone + std::fmt::Debug,
+{
+    /// The provider and the debug provider, if an RPC is available.
+    providers: Option<(P, P)>,
+    host_executor: HostExecutor<C::EvmConfig, C::ChainSpec>,
+    client_executor: Arc<ClientExecutor<C::EvmConfig, C::ChainSpec>>,
+    input_store: Option<Arc<dyn InputStore>>,
+    chain_config_hash: B256,
+    config: Config,
+}
+
+impl<C, P> NativeExecutor<C, P>
+where
+    C: ExecutorComponents,
+    P: Provider<C::Network> + Clone + std::fmt::Debug,
+{
+    pub fn try_new(
+        provider: Option<P>,
+        debug_provider: Option<P>,
+        evm_config: C::EvmConfig,
+        config: Config,
+    ) -> eyre::Result<Self> {
+        let input_store = config.input_store()?;
+        if provider.is_none() && input_store.is_none() {
+            bail!("Either a RPC URL, a cache dir or an input store URL must be provided")
+        }
+
+        let chain_spec = Arc::new(C::try_into_chain_spec(&config.genesis)?);
+        let providers = provider.map(|provider| {
+            let debug_provider = debug_provider.unwrap_or(provider.clone());
+            (provider, debug_provider)
+        });
+
+        Ok(Self {
+            providers,
+            host_executor: HostExecutor::new(evm_config, chain_spec.clone())
+                .with_fetch_config(config.fetch_config)
+                .with_rpc_cache(NonZeroUsize::new(config.rpc_cache_size).map(RpcCache::new))
+                .with_witness_source(config.witness_source)
+                .with_state_mismatch_diagnostics(config.diagnose_state_mismatch)
+                .with_rpc_urls(config.rpc_url.as_ref(), config.debug_rpc_url.as_ref()),
+            client_executor: Arc::new(C::client_executor(chain_spec, config.custom_beneficiary)),
+            input_store,
+            chain_config_hash: expected_chain_config_hash(
+                &config.genesis,
+                config.custom_beneficiary,
+            )?,
+            config,
+        })
+    }
+
+    /// Executes the block with the given block number natively.
+    pub async fn execute(&self, block_number: u64) -> eyre::Result<BlockPublicValues> {
+        self.execute_pinned_batch(block_number, block_number, None).await
+    }
+
+    /// Executes the blocks from `from_block` to `to_block` (inclusive) natively, the last one
+    /// being the block with the hash `to_block_hash` if set and the canonical one otherwise.
+    ///
+    /// Returns the public values the guest program would commit for the same input. When no RPC
+    /// is available, the hash of the block is required only if the inputs of several forks are
+    /// cached.
+    pub async fn execute_pinned_batch(
+        &self,
+        from_block: u64,
+        to_block: u64,
+        to_block_hash: Option<B256>,
+    ) -> eyre::Result<BlockPublicValues> {
+        let client_input = match &self.providers {
+            Some((provider, debug_provider)) => {
+                load_or_generate_input::<C, P>(
+                    &self.host_executor,
+                    provider,
+                    debug_provider,
+                    self.input_store.as_deref(),
+                    &self.config,
+                    from_block,
+                    to_block,
+                    to_block_hash,
+                )
+                .await?
+            }
+            None => {
+                let input_store = self.input_store.as_deref().expect("checked in try_new");
+
+                load_cached_input::<C::Primitives>(
+                    input_store,
+                    self.config.chain.id(),
+                    from_block,
+                    to_block,
+                    to_block_hash,
+                )
+                .await?
+            }
+        };
+
+        self.execute_input(client_input).await
+    }
+
+    /// Executes a client input natively, checking that the derived header of its last block
+    /// matches the one of the input.
+    pub async fn execute_input(
+        &self,
+        client_input: ClientExecutorInput<C::Primitives>,
+    ) -> eyre::Result<BlockPublicValues> {
+        let number = client_input.current_block.number;
+        let parent_hash = client_input.current_block.parent_hash;
+        let expected_header = client_input.last_block().header.clone();
+        let client_executor = self.client_executor.clone();
+
+        let now = Instant::now();
+        // The execution is CPU bound, so run it in a separate thread.
+        let (header, prev_state_root) = task::spawn_blocking(move || {
+            info_span!("execute_native", number).in_scope(|| client_executor.execute(client_input))
+        })
+        .await
+        .map_err(|err| eyre::eyre!("{err}"))??;
+        let elapsed = now.elapsed();
+
+        let block_hash = header.hash_slow();
+        let expected_block_hash = expected_header.hash_slow();
+        if block_hash != expected_block_hash {
+            let diff = HeaderDiff::new(&header, &expected_header);
+            return Err(HostError::HeaderMismatch(block_hash, expected_block_hash, diff).into());
+        }
+
+        let public_values = BlockPublicValues::new(
+            self.config.chain.id(),
+            self.chain_config_hash,
+            number,
+            parent_hash,
+            &header,
+            prev_state_root,
+        );
+        info!(
+            parent_hash = ?public_values.parent_hash,
+            block_hash = ?public_values.block_hash,
+            state_root = ?public_values.state_root,
+            "Native execution successful in {:?}",
+            elapsed
+        );
+
+        Ok(public_values)
+    }
+}
+
+impl<C, P> Debug for NativeExecutor<C, P>
+where
+    C: ExecutorComponents,
+    P: Provider<C::Network> + Clone + std::fmt::Debug,
+{
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("NativeExecutor")
+            .field("input_store", &self.input_store)
+            .field("config", &self.config)
+            .finish()
+    }
+}
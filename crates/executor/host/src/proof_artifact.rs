@@ -0,0 +1,111 @@
+use std::{
+    fs::File,
+    io::BufReader,
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use eyre::{bail, eyre};
+use revm_primitives::B256;
+use serde::{Deserialize, Serialize};
+use zkm_prover::components::DefaultProverComponents;
+use zkm_sdk::{
+    HashableKey, Prover, ZKMProof, ZKMProofWithPublicValues, ZKMPublicValues, ZKMVerifyingKey,
+};
+
+use crate::{utils::write_file, ProofKind};
+
+/// The program a proof was generated by.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ProofProgram {
+    /// The guest program executing the blocks, committing [BlockPublicValues].
+    ///
+    /// [BlockPublicValues]: guest_executor::io::BlockPublicValues
+    #[default]
+    Block,
+    /// The aggregation program verifying the block proofs, committing
+    /// [AggregationPublicValues].
+    ///
+    /// [AggregationPublicValues]: guest_executor::io::AggregationPublicValues
+    Aggregation,
+}
+
+/// A proof persisted to disk, along with the metadata needed to verify it offline.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProofArtifact {
+    /// The chain id.
+    pub chain_id: u64,
+    /// The number of the first proven block.
+    pub from_block: u64,
+    /// The number of the last proven block.
+    pub to_block: u64,
+    /// The hash of the last proven block.
+    pub block_hash: B256,
+    /// The program the proof was generated by.
+    pub program: ProofProgram,
+    /// The hash of the verifying key of the program, as returned by [HashableKey::bytes32].
+    pub vk_hash: String,
+    /// The kind of the proof.
+    pub proof_kind: ProofKind,
+    /// The version of Ziren used to generate the proof.
+    pub zkm_version: String,
+    /// The number of cycles of the proven execution.
+    pub cycles: u64,
+    /// The time taken to generate the proof.
+    pub proving_duration: Duration,
+    /// The bincode serialized [ZKMProof].
+    pub proof: Vec<u8>,
+    /// The bincode serialized [ZKMPublicValues].
+    pub public_values: Vec<u8>,
+}
+
+impl ProofArtifact {
+    /// Returns the path of the artifact in the given directory, keyed by the proven block range
+    /// so that the proofs of overlapping ranges don't overwrite each other.
+    pub fn path(&self, dir: &Path) -> PathBuf {
+        let prefix = match self.program {
+            ProofProgram::Block => "",
+            ProofProgram::Aggregation => "aggregation-",
+        };
+
+        dir.join(format!("{}/{prefix}{}-{}.bin", self.chain_id, self.from_block, self.to_block))
+    }
+
+    /// Saves the artifact in the given directory, returning its path.
+    pub fn save(&self, dir: &Path) -> eyre::Result<PathBuf> {
+        let path = self.path(dir);
+        write_file(&path, |writer| Ok(bincode::serialize_into(writer, self)?))?;
+
+        Ok(path)
+    }
+
+    /// Loads an artifact from the given file.
+    pub fn load(path: &Path) -> eyre::Result<Self> {
+        let reader = BufReader::new(File::open(path)?);
+        let artifact = bincode::deserialize_from(reader)?;
+
+        Ok(artifact)
+    }
+
+    /// Returns the proof along with its public values.
+    pub fn proof_with_public_values(&self) -> eyre::Result<ZKMProofWithPublicValues> {
+        let proof: ZKMProof = bincode::deserialize(&self.proof)?;
+        let public_values: ZKMPublicValues = bincode::deserialize(&self.public_values)?;
+
+        Ok(ZKMProofWithPublicValues { proof, public_values, zkm_version: self.zkm_version.clone() })
+    }
+
+    /// Verifies the proof against the given verifying key.
+    pub fn verify<P>(&self, client: &P, vk: &ZKMVerifyingKey) -> eyre::Result<()>
+    where
+        P: Prover<DefaultProverComponents>,
+    {
+        let vk_hash = vk.bytes32();
+        if vk_hash != self.vk_hash {
+            bail!("Verifying key mismatch: found {}, expected {}", vk_hash, self.vk_hash);
+        }
+
+        let proof = self.proof_with_public_values()?;
+        client.verify(&proof, vk).map_err(|err| eyre!("Failed to verify the proof: {err}"))
+    }
+}
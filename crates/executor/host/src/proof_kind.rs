@@ -0,0 +1,39 @@
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+use zkm_sdk::{ZKMProof, ZKMProofKind};
+
+/// The kind of proof to generate.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, ValueEnum)]
+pub enum ProofKind {
+    /// A proof for each shard of the execution.
+    Core,
+    /// A single recursive proof of constant size.
+    #[default]
+    Compressed,
+    /// A Groth16 wrapped proof, verifiable on-chain.
+    Groth16,
+    /// A PLONK wrapped proof, verifiable on-chain.
+    Plonk,
+}
+
+impl From<ProofKind> for ZKMProofKind {
+    fn from(value: ProofKind) -> Self {
+        match value {
+            ProofKind::Core => ZKMProofKind::Core,
+            ProofKind::Compressed => ZKMProofKind::Compressed,
+            ProofKind::Groth16 => ZKMProofKind::Groth16,
+            ProofKind::Plonk => ZKMProofKind::Plonk,
+        }
+    }
+}
+
+impl From<&ZKMProof> for ProofKind {
+    fn from(value: &ZKMProof) -> Self {
+        match value {
+            ZKMProof::Core(_) => ProofKind::Core,
+            ZKMProof::Compressed(_) => ProofKind::Compressed,
+            ZKMProof::Groth16(_) => ProofKind::Groth16,
+            ZKMProof::Plonk(_) => ProofKind::Plonk,
+        }
+    }
+}
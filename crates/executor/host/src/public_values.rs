@@ -0,0 +1,95 @@
+use guest_executor::io::{AggregationPublicValues, BlockPublicValues};
+use primitives::{chain_spec, genesis::Genesis};
+use revm_primitives::{Address, B256};
+use zkm_sdk::ZKMPublicValues;
+
+use crate::HostError;
+
+/// Decodes the public values committed by the guest programs.
+///
+/// The version is checked before decoding, so that proofs generated by an incompatible guest
+/// program are rejected with a meaningful error.
+pub fn decode_public_values(
+    public_values: &ZKMPublicValues,
+) -> Result<BlockPublicValues, HostError> {
+    decode_public_values_bytes(public_values.as_slice())
+}
+
+/// Decodes the raw bytes of the public values committed by the guest programs.
+pub fn decode_public_values_bytes(bytes: &[u8]) -> Result<BlockPublicValues, HostError> {
+    BlockPublicValues::decode(bytes).map_err(|err| HostError::InvalidPublicValues(err.to_string()))
+}
+
+/// Decodes the public values committed by the aggregation program.
+pub fn decode_aggregation_public_values(
+    public_values: &ZKMPublicValues,
+) -> Result<AggregationPublicValues, HostError> {
+    AggregationPublicValues::decode(public_values.as_slice())
+        .map_err(|err| HostError::InvalidPublicValues(err.to_string()))
+}
+
+/// Returns the chain configuration hash that the guest programs commit when executing blocks of
+/// the given [Genesis] with the given beneficiary override.
+pub fn expected_chain_config_hash(
+    genesis: &Genesis,
+    custom_beneficiary: Option<Address>,
+) -> Result<B256, HostError> {
+    chain_spec::expected_chain_config_hash(genesis, custom_beneficiary)
+        .map_err(|err| HostError::Custom(err.to_string()))
+}
+
+/// Checks that the public values were committed with the expected chain configuration.
+pub fn verify_chain_config(
+    public_values: &BlockPublicValues,
+    genesis: &Genesis,
+    custom_beneficiary: Option<Address>,
+) -> Result<(), HostError> {
+    verify_chain_config_hash(public_values.chain_config_hash, genesis, custom_beneficiary)
+}
+
+/// Checks that a committed chain configuration hash is the expected one.
+pub fn verify_chain_config_hash(
+    chain_config_hash: B256,
+    genesis: &Genesis,
+    custom_beneficiary: Option<Address>,
+) -> Result<(), HostError> {
+    let expected = expected_chain_config_hash(genesis, custom_beneficiary)?;
+
+    if chain_config_hash != expected {
+        return Err(HostError::ChainConfigMismatch(chain_config_hash, expected));
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use alloy_consensus::Header;
+
+    use guest_executor::io::PUBLIC_VALUES_VERSION;
+
+    use super::*;
+
+    #[test]
+    fn test_decode_public_values() {
+        let header = Header { number: 42, timestamp: 1_700_000_000, ..Default::default() };
+        let chain_config_hash = expected_chain_config_hash(&Genesis::Mainnet, None).unwrap();
+        let public_values = BlockPublicValues::new(
+            1,
+            chain_config_hash,
+            42,
+            B256::repeat_byte(1),
+            &header,
+            B256::ZERO,
+        );
+
+        let mut bytes = bincode::serialize(&public_values).unwrap();
+        assert_eq!(decode_public_values_bytes(&bytes).unwrap(), public_values);
+        assert!(verify_chain_config(&public_values, &Genesis::Mainnet, None).is_ok());
+        assert!(verify_chain_config(&public_values, &Genesis::Sepolia, None).is_err());
+
+        bytes[0] = PUBLIC_VALUES_VERSION + 1;
+        assert!(decode_public_values_bytes(&bytes).is_err());
+        assert!(decode_public_values_bytes(&[]).is_err());
+    }
+}
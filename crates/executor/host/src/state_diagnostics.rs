@@ -0,0 +1,169 @@
+use std::fmt;
+
+use alloy_consensus::EMPTY_ROOT_HASH;
+use alloy_eips::BlockId;
+use alloy_primitives::{keccak256, Address, B256, U256};
+use alloy_provider::{Network, Provider};
+use futures::{stream, StreamExt, TryStreamExt};
+use mpt::EthereumState;
+use reth_trie::TrieAccount;
+use revm::database::BundleState;
+use revm_primitives::KECCAK_EMPTY;
+use tracing::{error, warn};
+
+use crate::HostError;
+
+/// The leaf of an account missing from the state trie.
+const EMPTY_ACCOUNT: TrieAccount = TrieAccount {
+    nonce: 0,
+    balance: U256::ZERO,
+    storage_root: EMPTY_ROOT_HASH,
+    code_hash: KECCAK_EMPTY,
+};
+
+/// An account whose post-execution state diverges from the one reported by the RPC.
+#[derive(Debug)]
+struct AccountDivergence {
+    address: Address,
+    /// The leaf computed from the execution output, `None` if the account was destroyed.
+    computed: Option<TrieAccount>,
+    /// The leaf reported by the RPC.
+    expected: TrieAccount,
+    /// The modified slots whose value diverges, with the computed and the expected value.
+    slots: Vec<(U256, U256, U256)>,
+}
+
+impl fmt::Display for AccountDivergence {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "account {} diverges:", self.address)?;
+
+        let computed = self.computed.as_ref().unwrap_or(&EMPTY_ACCOUNT);
+        let fields = [
+            ("nonce", computed.nonce.to_string(), self.expected.nonce.to_string()),
+            ("balance", computed.balance.to_string(), self.expected.balance.to_string()),
+            (
+                "storage root",
+                computed.storage_root.to_string(),
+                self.expected.storage_root.to_string(),
+            ),
+            ("code hash", computed.code_hash.to_string(), self.expected.code_hash.to_string()),
+        ];
+        for (name, computed, expected) in fields {
+            if computed != expected {
+                writeln!(f, "  {name}: computed {computed}, expected {expected}")?;
+            }
+        }
+
+        for (slot, computed, expected) in &self.slots {
+            writeln!(f, "  slot {slot:#x}: computed {computed}, expected {expected}")?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Logs the touched accounts and slots whose post-execution value diverges from the one reported
+/// by `eth_getProof` at the block `block_hash`, so that it is compared with the executed fork.
+///
+/// Diverging values point to an EVM bug or to a provider serving an invalid pre-state, while a
+/// mismatch without any divergence points to invalid trie nodes in the witness.
+pub(crate) async fn report_state_root_mismatch<P, N>(
+    provider: &P,
+    block_number: u64,
+    block_hash: B256,
+    bundle_state: &BundleState,
+    post_state: &EthereumState,
+    max_concurrent_requests: usize,
+) where
+    P: Provider<N>,
+    N: Network,
+{
+    error!("[{}] diagnosing the state root mismatch", block_number);
+
+    match diagnose(provider, block_hash, bundle_state, post_state, max_concurrent_requests).await {
+        Ok(divergences) if divergences.is_empty() => {
+            error!(
+                "[{}] the {} touched accounts match the RPC state, the witness trie nodes are likely invalid",
+                block_number,
+                bundle_state.state.len()
+            );
+        }
+        Ok(divergences) => {
+            for divergence in divergences {
+                error!("[{}] {}", block_number, divergence);
+            }
+        }
+        Err(err) => warn!("[{}] failed to diagnose the state root mismatch: {}", block_number, err),
+    }
+}
+
+async fn diagnose<P, N>(
+    provider: &P,
+    block_hash: B256,
+    bundle_state: &BundleState,
+    post_state: &EthereumState,
+    max_concurrent_requests: usize,
+) -> Result<Vec<AccountDivergence>, HostError>
+where
+    P: Provider<N>,
+    N: Network,
+{
+    let divergences = stream::iter(bundle_state.state.iter())
+        .map(|(address, account)| async move {
+            let slots = account.storage.keys().copied().collect::<Vec<_>>();
+            let proof = provider
+                .get_proof(*address, slots.iter().map(|slot| B256::from(*slot)).collect())
+                .block_id(BlockId::hash(block_hash))
+                .await?;
+
+            // The providers report missing accounts with zero hashes.
+            let expected = TrieAccount {
+                nonce: proof.nonce,
+                balance: proof.balance,
+                storage_root: if proof.storage_hash.is_zero() {
+                    EMPTY_ROOT_HASH
+                } else {
+                    proof.storage_hash
+                },
+                code_hash: if proof.code_hash.is_zero() { KECCAK_EMPTY } else { proof.code_hash },
+            };
+            let computed = account.info.as_ref().map(|info| TrieAccount {
+                nonce: info.nonce,
+                balance: info.balance,
+                storage_root: post_state
+                    .storage_tries
+                    .get(&keccak256(address))
+                    .map(|trie| trie.hash())
+                    .unwrap_or(EMPTY_ROOT_HASH),
+                code_hash: info.code_hash,
+            });
+
+            let slots = slots
+                .iter()
+                .zip(&proof.storage_proof)
+                .filter_map(|(slot, storage_proof)| {
+                    let computed = account.storage[slot].present_value;
+                    (computed != storage_proof.value).then_some((
+                        *slot,
+                        computed,
+                        storage_proof.value,
+                    ))
+                })
+                .collect::<Vec<_>>();
+
+            let diverges =
+                computed.as_ref().unwrap_or(&EMPTY_ACCOUNT) != &expected || !slots.is_empty();
+
+            Ok::<_, HostError>(diverges.then_some(AccountDivergence {
+                address: *address,
+                computed,
+                expected,
+                slots,
+            }))
+        })
+        .buffered(max_concurrent_requests.max(1))
+        .try_collect::<Vec<_>>()
+        .await?;
+
+    Ok(divergences.into_iter().flatten().collect())
+}
@@ -1,5 +1,15 @@
+use std::{
+    fs::File,
+    io::BufWriter,
+    path::Path,
+    sync::atomic::{AtomicU64, Ordering},
+};
+
 use zkm_sdk::ZKMStdin;
 
+/// Distinguishes the temporary files written concurrently by the same process.
+static TMP_FILE_COUNTER: AtomicU64 = AtomicU64::new(0);
+
 /// Dump the program and stdin to files for debugging if `ZKM_DUMP` is set.
 pub(crate) fn zkm_dump(elf: &[u8], stdin: &ZKMStdin, block: u64) {
     if std::env::var("ZKM_DUMP").map(|v| v == "1" || v.to_lowercase() == "true").unwrap_or(false) {
@@ -8,3 +18,58 @@ pub(crate) fn zkm_dump(elf: &[u8], stdin: &ZKMStdin, block: u64) {
         std::fs::write(format!("{block}-stdin.bin"), stdin.clone()).unwrap();
     }
 }
+
+/// Writes a file with the given function, creating its directory if needed.
+///
+/// The file is written to a temporary file first, then renamed, so that a partially written file
+/// is never loaded. The name of the temporary file is unique so that the processes sharing a
+/// directory don't write to the same file, and it is removed if the write fails.
+pub(crate) fn write_file(
+    path: &Path,
+    write: impl FnOnce(&mut BufWriter<File>) -> eyre::Result<()>,
+) -> eyre::Result<()> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+
+    let tmp_path = path.with_extension(format!(
+        "{}-{}.tmp",
+        std::process::id(),
+        TMP_FILE_COUNTER.fetch_add(1, Ordering::Relaxed)
+    ));
+    let result = (|| -> eyre::Result<()> {
+        let mut writer = BufWriter::new(File::create(&tmp_path)?);
+        write(&mut writer)?;
+        writer.into_inner().map_err(|err| err.into_error())?.sync_all()?;
+        std::fs::rename(&tmp_path, path)?;
+        Ok(())
+    })();
+
+    if result.is_err() {
+        let _ = std::fs::remove_file(&tmp_path);
+    }
+
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_write_file() {
+        let dir = std::env::temp_dir().join(format!("write-file-{}", std::process::id()));
+        let path = dir.join("nested/file.bin");
+
+        write_file(&path, |writer| Ok(std::io::Write::write_all(writer, &[1, 2, 3])?)).unwrap();
+        assert_eq!(std::fs::read(&path).unwrap(), vec![1, 2, 3]);
+
+        // A failed write leaves neither the file nor the temporary file behind.
+        let other_path = dir.join("nested/other.bin");
+        assert!(write_file(&other_path, |_| eyre::bail!("failed")).is_err());
+        assert!(!other_path.exists());
+        assert_eq!(std::fs::read_dir(dir.join("nested")).unwrap().count(), 1);
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,14 @@
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+
+/// Where the witness of the state accessed by the blocks is fetched from.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, ValueEnum)]
+pub enum WitnessSource {
+    /// Uses `debug_executionWitness` if the debug RPC supports it, `eth_getProof` otherwise.
+    Auto,
+    /// Fetches the accessed state lazily and proves it with `eth_getProof`.
+    #[default]
+    Proofs,
+    /// Fetches the whole witness at once with `debug_executionWitness`.
+    ExecutionWitness,
+}
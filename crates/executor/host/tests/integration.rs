@@ -1,4 +1,7 @@
-use std::sync::Arc;
+use std::{
+    path::{Path, PathBuf},
+    sync::Arc,
+};
 
 use alloy_provider::{network::Ethereum, Network, RootProvider};
 use guest_executor::{
@@ -6,12 +9,16 @@ use guest_executor::{
     io::ClientExecutorInput,
     BlockValidator, FromInput, IntoInput, IntoPrimitives,
 };
-use host_executor::{EthHostExecutor, HostExecutor};
+use host_executor::{
+    create_eth_block_execution_strategy_factory, Config, EthExecutorComponents, EthHostExecutor,
+    HostExecutor, NativeExecutor,
+};
 use primitives::genesis::Genesis;
-use reth_chainspec::ChainSpec;
+use provider::{create_recording_provider, create_replay_provider, RetryConfig, RpcRecorder};
+use reth_chainspec::{ChainSpec, EthChainSpec};
 use reth_evm::ConfigureEvm;
 use reth_optimism_chainspec::OpChainSpec;
-use revm_primitives::{address, Address};
+use revm_primitives::{address, Address, B256};
 use serde::{de::DeserializeOwned, Serialize};
 use tracing_subscriber::{
     fmt, prelude::__tracing_subscriber_SubscriberExt, util::SubscriberInitExt, EnvFilter,
@@ -100,6 +107,7 @@ async fn run_e2e<C, CS, N>(
         + BlockValidator<CS>
         + Serialize
         + DeserializeOwned,
+    CS: EthChainSpec,
     N: Network,
 {
     // Intialize the environment variables.
@@ -111,10 +119,7 @@ async fn run_e2e<C, CS, N>(
         .with(EnvFilter::from_default_env())
         .try_init();
 
-    // Setup the provider.
-    let rpc_url =
-        Url::parse(std::env::var(env_var_key).unwrap().as_str()).expect("invalid rpc url");
-    let provider = RootProvider::<N>::new_http(rpc_url);
+    let (provider, recording) = setup_provider::<N>(env_var_key, block_number).await;
 
     // Execute the host.
     let client_input = host_executor
@@ -122,6 +127,10 @@ async fn run_e2e<C, CS, N>(
         .await
         .expect("failed to execute host");
 
+    if let Some((recorder, fixture_path)) = recording {
+        recorder.save(&fixture_path).expect("failed to save the rpc fixture");
+    }
+
     // Execute the client.
     client_executor.execute(client_input.clone()).expect("failed to execute client");
 
@@ -131,3 +140,75 @@ async fn run_e2e<C, CS, N>(
     // Load the client input from a buffer.
     let _: ClientExecutorInput<C::Primitives> = bincode::deserialize(&buffer).unwrap();
 }
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_native_execution() {
+    let block_number = 18884864;
+    let config = Config::mainnet();
+    let chain_spec: Arc<ChainSpec> = Arc::new((&config.genesis).try_into().unwrap());
+    let host_executor = EthHostExecutor::eth(chain_spec, None);
+
+    // The same block as the Ethereum e2e test, to share its fixture.
+    let (provider, recording) = setup_provider::<Ethereum>("RPC_1", block_number).await;
+    let client_input = host_executor
+        .execute(block_number, &provider, &provider, config.genesis.clone(), None, false)
+        .await
+        .expect("failed to execute host");
+    if let Some((recorder, fixture_path)) = recording {
+        recorder.save(&fixture_path).expect("failed to save the rpc fixture");
+    }
+
+    let evm_config = create_eth_block_execution_strategy_factory(&config.genesis, None);
+    let native_executor = NativeExecutor::<EthExecutorComponents<()>, _>::try_new(
+        Some(provider),
+        None,
+        evm_config,
+        config,
+    )
+    .unwrap();
+
+    let block_hash = client_input.current_block.header.hash_slow();
+    let public_values = native_executor
+        .execute_input(client_input.clone())
+        .await
+        .expect("failed to execute natively");
+    assert_eq!(public_values.block_number, block_number);
+    assert_eq!(public_values.block_hash, block_hash);
+
+    // An input whose block doesn't match its state is rejected.
+    let mut tampered_input = client_input;
+    tampered_input.current_block.header.state_root = B256::ZERO;
+    assert!(native_executor.execute_input(tampered_input).await.is_err());
+}
+
+/// Returns a provider replaying the recorded RPC responses of the block if the fixture exists.
+///
+/// No fixture is committed yet, so the e2e tests still query the RPC given by `env_var_key` and
+/// need network access. Setting `RECORD_RPC_FIXTURES` records the fixture from the RPC, the
+/// recorder and the path of the fixture being returned to save it once done.
+async fn setup_provider<N: Network>(
+    env_var_key: &str,
+    block_number: u64,
+) -> (RootProvider<N>, Option<(RpcRecorder, PathBuf)>) {
+    let fixture_path = Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/fixtures")
+        .join(format!("{env_var_key}-{block_number}.json"));
+    let record = std::env::var("RECORD_RPC_FIXTURES").is_ok();
+    if fixture_path.exists() && !record {
+        let provider =
+            create_replay_provider::<N>(&fixture_path).expect("failed to load the rpc fixture");
+        return (provider, None);
+    }
+
+    let recorder = RpcRecorder::new();
+    let rpc_url = std::env::var(env_var_key).unwrap_or_else(|_| {
+        panic!("no rpc fixture at {} and {env_var_key} is not set", fixture_path.display())
+    });
+    let rpc_url = Url::parse(&rpc_url).expect("invalid rpc url");
+    let provider =
+        create_recording_provider::<N>(vec![rpc_url], &RetryConfig::default(), &recorder)
+            .await
+            .expect("failed to connect to the rpc");
+
+    (provider, Some((recorder, fixture_path)))
+}
@@ -3,7 +3,10 @@ use alloy_rlp::Decodable;
 use alloy_rpc_types_debug::ExecutionWitness;
 use reth_trie::TrieAccount;
 
-use crate::mpt::{resolve_nodes, MptNode, MptNodeData, MptNodeReference};
+use crate::{
+    mpt::{resolve_nodes, MptNode, MptNodeData, MptNodeReference},
+    FromProofError,
+};
 
 // Builds tries from the witness state.
 //
@@ -12,7 +15,7 @@ use crate::mpt::{resolve_nodes, MptNode, MptNodeData, MptNodeReference};
 pub(crate) fn build_validated_tries(
     witness: &ExecutionWitness,
     pre_state_root: B256,
-) -> Result<(MptNode, HashMap<B256, MptNode>), String> {
+) -> Result<(MptNode, HashMap<B256, MptNode>), FromProofError> {
     // Step 1: Decode all RLP-encoded trie nodes and index by hash
     // IMPORTANT: Witness state contains both *state trie* nodes and *storage tries* nodes!
     let mut node_map: HashMap<MptNodeReference, MptNode> = HashMap::default();
@@ -20,7 +23,7 @@ pub(crate) fn build_validated_tries(
     let mut root_node: Option<MptNode> = None;
 
     for encoded in &witness.state {
-        let node = MptNode::decode(encoded).expect("Valid MPT node in witness");
+        let node = MptNode::decode(encoded)?;
         let hash = keccak256(encoded);
         if hash == pre_state_root {
             root_node = Some(node.clone());
@@ -36,12 +39,24 @@ pub(crate) fn build_validated_tries(
     let mut raw_storage_tries = vec![];
     let state_trie = resolve_nodes(&root, &node_map);
 
+    let mut invalid_leaf = None;
+
     state_trie.for_each_leaves(|key, mut value| {
-        let account = TrieAccount::decode(&mut value).unwrap();
         let hashed_address = B256::from_slice(key);
-        raw_storage_tries.push((hashed_address, account.storage_root));
+        match TrieAccount::decode(&mut value) {
+            Ok(account) => raw_storage_tries.push((hashed_address, account.storage_root)),
+            Err(err) => {
+                invalid_leaf.get_or_insert(FromProofError::InvalidExecutionWitness(format!(
+                    "invalid account leaf {hashed_address}: {err}"
+                )));
+            }
+        }
     });
 
+    if let Some(err) = invalid_leaf {
+        return Err(err);
+    }
+
     // Step 3: Build storage tries per account efficiently
     let mut storage_tries: HashMap<B256, MptNode> = HashMap::default();
 
@@ -58,7 +73,9 @@ pub(crate) fn build_validated_tries(
         let storage_trie = resolve_nodes(&root_node, &node_map);
 
         if storage_trie.is_digest() {
-            panic!("Could not resolve storage trie for {storage_root}");
+            return Err(FromProofError::InvalidExecutionWitness(format!(
+                "could not resolve storage trie for {storage_root}"
+            )));
         }
 
         // Insert resolved storage trie.
@@ -66,19 +83,23 @@ pub(crate) fn build_validated_tries(
     }
 
     // Step 3a: Verify that state_trie was built correctly - confirm tree hash with pre state root.
-    validate_state_trie(&state_trie, pre_state_root);
+    validate_state_trie(&state_trie, pre_state_root)?;
 
     // Step 3b: Verify that each storage trie matches the declared storage_root in the state trie.
-    validate_storage_tries(&state_trie, &storage_tries)?;
+    validate_storage_tries(&state_trie, &storage_tries)
+        .map_err(FromProofError::InvalidExecutionWitness)?;
 
     Ok((state_trie, storage_tries))
 }
 
 // Validate that state_trie was built correctly - confirm tree hash with pre state root.
-fn validate_state_trie(state_trie: &MptNode, pre_state_root: B256) {
-    if state_trie.hash() != pre_state_root {
-        panic!("Computed state root does not match pre_state_root");
+fn validate_state_trie(state_trie: &MptNode, pre_state_root: B256) -> Result<(), FromProofError> {
+    let state_root = state_trie.hash();
+    if state_root != pre_state_root {
+        return Err(FromProofError::MismatchedStateRoot(state_root, pre_state_root));
     }
+
+    Ok(())
 }
 
 // Validates that each storage trie matches the declared storage_root in the state trie.
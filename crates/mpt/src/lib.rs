@@ -76,11 +76,11 @@ impl EthereumState {
     pub fn from_execution_witness(
         witness: &alloy_rpc_types_debug::ExecutionWitness,
         pre_state_root: B256,
-    ) -> Self {
+    ) -> Result<Self, FromProofError> {
         let (state_trie, storage_tries) =
-            execution_witness::build_validated_tries(witness, pre_state_root).unwrap();
+            execution_witness::build_validated_tries(witness, pre_state_root)?;
 
-        Self { state_trie, storage_tries }
+        Ok(Self { state_trie, storage_tries })
     }
 
     /// Mutates state based on diffs provided in [`HashedPostState`].
@@ -158,6 +158,8 @@ pub enum FromProofError {
     #[error("Found mismatched state root after reconstruction \n found {}, expected {}", .0, .1)]
     MismatchedStateRoot(B256, B256),
     // todo: Should decode return a decoder error?
+    #[error("Invalid execution witness: {}", .0)]
+    InvalidExecutionWitness(String),
     #[error("Error decoding proofs from bytes, {}", .0)]
     DecodingError(#[from] Error),
 }
@@ -1,4 +1,6 @@
-use reth_chainspec::ChainSpec;
+use alloy_eips::eip7840::BlobParams;
+use alloy_primitives::{keccak256, Address, B256};
+use reth_chainspec::{BaseFeeParams, BaseFeeParamsKind, ChainSpec, ForkCondition};
 
 use crate::genesis::Genesis;
 
@@ -23,6 +25,134 @@ pub fn sepolia() -> eyre::Result<ChainSpec> {
     (&Genesis::Sepolia).try_into()
 }
 
+/// Returns the canonical hash of the chain configuration used to execute blocks: the chain id,
+/// the genesis hash, the hardfork schedule, the base fee and blob parameters, the deposit contract
+/// and the custom beneficiary override.
+///
+/// The guest programs commit this hash, so that a verifier can check that a proof was generated
+/// with the expected chain configuration.
+pub fn chain_config_hash(chain_spec: &ChainSpec, custom_beneficiary: Option<Address>) -> B256 {
+    let mut buf = Vec::new();
+
+    buf.extend_from_slice(&chain_spec.chain.id().to_be_bytes());
+    buf.extend_from_slice(chain_spec.genesis_hash().as_slice());
+
+    match chain_spec.paris_block_and_final_difficulty {
+        Some((block, difficulty)) => {
+            buf.push(1);
+            buf.extend_from_slice(&block.to_be_bytes());
+            buf.extend_from_slice(&difficulty.to_be_bytes::<32>());
+        }
+        None => buf.push(0),
+    }
+
+    let forks = chain_spec.hardforks.forks_iter().collect::<Vec<_>>();
+    buf.extend_from_slice(&(forks.len() as u64).to_be_bytes());
+    for (fork, condition) in forks {
+        encode_str(&mut buf, fork.name());
+        encode_fork_condition(&mut buf, condition);
+    }
+
+    match &chain_spec.base_fee_params {
+        BaseFeeParamsKind::Constant(params) => {
+            buf.push(0);
+            encode_base_fee_params(&mut buf, params);
+        }
+        BaseFeeParamsKind::Variable(params) => {
+            buf.push(1);
+            buf.extend_from_slice(&(params.0.len() as u64).to_be_bytes());
+            for (fork, params) in params.0.iter() {
+                encode_str(&mut buf, fork.name());
+                encode_base_fee_params(&mut buf, params);
+            }
+        }
+    }
+
+    let blob_params = &chain_spec.blob_params;
+    encode_blob_params(&mut buf, &blob_params.cancun);
+    encode_blob_params(&mut buf, &blob_params.prague);
+    encode_blob_params(&mut buf, &blob_params.osaka);
+    buf.extend_from_slice(&(blob_params.scheduled.len() as u64).to_be_bytes());
+    for (timestamp, params) in &blob_params.scheduled {
+        buf.extend_from_slice(&timestamp.to_be_bytes());
+        encode_blob_params(&mut buf, params);
+    }
+
+    match &chain_spec.deposit_contract {
+        Some(deposit_contract) => {
+            buf.push(1);
+            buf.extend_from_slice(deposit_contract.address.as_slice());
+            buf.extend_from_slice(&deposit_contract.block.to_be_bytes());
+            buf.extend_from_slice(deposit_contract.topic.as_slice());
+        }
+        None => buf.push(0),
+    }
+
+    match custom_beneficiary {
+        Some(beneficiary) => {
+            buf.push(1);
+            buf.extend_from_slice(beneficiary.as_slice());
+        }
+        None => buf.push(0),
+    }
+
+    keccak256(buf)
+}
+
+/// Returns the expected chain configuration hash for the given [Genesis].
+pub fn expected_chain_config_hash(
+    genesis: &Genesis,
+    custom_beneficiary: Option<Address>,
+) -> eyre::Result<B256> {
+    #[cfg(feature = "optimism")]
+    if genesis.is_optimism() {
+        let chain_spec: reth_optimism_chainspec::OpChainSpec = genesis.try_into()?;
+        return Ok(chain_config_hash(&chain_spec.inner, custom_beneficiary));
+    }
+
+    let chain_spec: ChainSpec = genesis.try_into()?;
+    Ok(chain_config_hash(&chain_spec, custom_beneficiary))
+}
+
+fn encode_str(buf: &mut Vec<u8>, value: &str) {
+    buf.extend_from_slice(&(value.len() as u64).to_be_bytes());
+    buf.extend_from_slice(value.as_bytes());
+}
+
+fn encode_fork_condition(buf: &mut Vec<u8>, condition: ForkCondition) {
+    match condition {
+        ForkCondition::Block(block) => {
+            buf.push(0);
+            buf.extend_from_slice(&block.to_be_bytes());
+        }
+        ForkCondition::TTD { activation_block_number, fork_block, total_difficulty } => {
+            buf.push(1);
+            buf.extend_from_slice(&activation_block_number.to_be_bytes());
+            buf.extend_from_slice(&fork_block.unwrap_or(u64::MAX).to_be_bytes());
+            buf.extend_from_slice(&total_difficulty.to_be_bytes::<32>());
+        }
+        ForkCondition::Timestamp(timestamp) => {
+            buf.push(2);
+            buf.extend_from_slice(&timestamp.to_be_bytes());
+        }
+        ForkCondition::Never => buf.push(3),
+    }
+}
+
+fn encode_base_fee_params(buf: &mut Vec<u8>, params: &BaseFeeParams) {
+    buf.extend_from_slice(&params.max_change_denominator.to_be_bytes());
+    buf.extend_from_slice(&params.elasticity_multiplier.to_be_bytes());
+}
+
+fn encode_blob_params(buf: &mut Vec<u8>, params: &BlobParams) {
+    buf.extend_from_slice(&params.target_blob_count.to_be_bytes());
+    buf.extend_from_slice(&params.max_blob_count.to_be_bytes());
+    buf.extend_from_slice(&params.update_fraction.to_be_bytes());
+    buf.extend_from_slice(&params.min_blob_fee.to_be_bytes());
+    buf.extend_from_slice(&params.max_blobs_per_tx.to_be_bytes());
+    buf.extend_from_slice(&params.blob_base_cost.to_be_bytes());
+}
+
 #[cfg(test)]
 mod tests {
     use crate::chain_spec::{linea_mainnet, sepolia};
@@ -30,7 +160,9 @@ mod tests {
     #[cfg(feature = "optimism")]
     use crate::chain_spec::op_mainnet;
 
-    use super::mainnet;
+    use super::{chain_config_hash, expected_chain_config_hash, mainnet};
+    use crate::genesis::Genesis;
+    use alloy_primitives::Address;
 
     #[test]
     pub fn test_mainnet_chain_spec() {
@@ -60,4 +192,32 @@ mod tests {
 
         assert_eq!(11155111, chain_spec.chain.id(), "the chain id must be 11155111 for Sepolia");
     }
+
+    #[test]
+    pub fn test_chain_config_hash() {
+        let chain_spec = mainnet().unwrap();
+        let hash = chain_config_hash(&chain_spec, None);
+
+        assert_eq!(hash, expected_chain_config_hash(&Genesis::Mainnet, None).unwrap());
+        assert_ne!(hash, expected_chain_config_hash(&Genesis::Sepolia, None).unwrap());
+        assert_ne!(
+            hash,
+            chain_config_hash(&chain_spec, Some(Address::repeat_byte(1))),
+            "the custom beneficiary must be bound to the hash"
+        );
+    }
+
+    #[test]
+    pub fn test_is_optimism() {
+        use crate::genesis::LINEA_GENESIS_JSON;
+
+        assert!(Genesis::OpMainnet.is_optimism());
+        assert!(!Genesis::Mainnet.is_optimism());
+        assert!(!Genesis::Custom(LINEA_GENESIS_JSON.to_string()).is_optimism());
+
+        let mut genesis: serde_json::Value = serde_json::from_str(LINEA_GENESIS_JSON).unwrap();
+        genesis["config"]["optimism"] =
+            serde_json::json!({ "eip1559Elasticity": 6, "eip1559Denominator": 50 });
+        assert!(Genesis::Custom(genesis.to_string()).is_optimism());
+    }
 }
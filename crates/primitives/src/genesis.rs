@@ -23,6 +23,19 @@ pub enum Genesis {
     Custom(String),
 }
 
+impl Genesis {
+    /// Returns whether the chain is an OP Stack chain, whose blocks are executed by the OP guest
+    /// program. A custom genesis is an OP Stack one when its config has an `optimism` section.
+    pub fn is_optimism(&self) -> bool {
+        match self {
+            Genesis::OpMainnet => true,
+            Genesis::Custom(json) => genesis_from_json(json)
+                .is_ok_and(|genesis| genesis.config.extra_fields.contains_key("optimism")),
+            _ => false,
+        }
+    }
+}
+
 /// Returns the [alloy_genesis::Genesis] fron a json string.
 pub fn genesis_from_json(json: &str) -> eyre::Result<alloy_genesis::Genesis> {
     let genesis = serde_json::from_str::<alloy_genesis::Genesis>(json)?;
@@ -103,7 +116,7 @@ impl TryFrom<&Genesis> for ChainSpec {
                 Ok(sepolia)
             }
             Genesis::OpMainnet => {
-                Err(eyre!("Only converting Genesis::OpMainnet to OpChainSpec is supported"))
+                Err(eyre!("Only converting OP Stack genesis to OpChainSpec is supported"))
             }
             Genesis::Linea => Ok(ChainSpec::from_genesis(genesis_from_json(LINEA_GENESIS_JSON)?)),
             Genesis::GOAT => Ok(ChainSpec::from_genesis(genesis_from_json(GOAT_GENESIS_JSON)?)),
@@ -138,7 +151,8 @@ impl TryFrom<&Genesis> for reth_optimism_chainspec::OpChainSpec {
 
                 Ok(op_mainnet)
             }
-            _ => Err(eyre!("Only converting Genesis::OpMainnet to OpChainSpec is supported")),
+            Genesis::Custom(json) if value.is_optimism() => Ok(genesis_from_json(json)?.into()),
+            _ => Err(eyre!("Only converting OP Stack genesis to OpChainSpec is supported")),
         }
     }
 }
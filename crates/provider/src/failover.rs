@@ -0,0 +1,184 @@
+use std::{
+    sync::{Arc, Mutex, PoisonError},
+    task::{Context, Poll},
+    time::{Duration, Instant},
+};
+
+use alloy_json_rpc::{RequestPacket, ResponsePacket};
+use alloy_transport::{BoxTransport, TransportError, TransportFut};
+use tower::Service;
+use tracing::warn;
+use url::Url;
+
+use crate::endpoint_name;
+
+/// How long a failing endpoint is skipped before being tried again first.
+const UNHEALTHY_COOLDOWN: Duration = Duration::from_secs(30);
+
+#[derive(Debug)]
+struct Endpoint {
+    /// See [endpoint_name].
+    name: String,
+    transport: BoxTransport,
+    /// Until when the endpoint is skipped, after a failure.
+    unhealthy_until: Mutex<Option<Instant>>,
+}
+
+impl Endpoint {
+    fn is_healthy(&self, now: Instant) -> bool {
+        self.unhealthy_until
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .is_none_or(|unhealthy_until| unhealthy_until <= now)
+    }
+
+    fn set_healthy(&self, healthy: bool) {
+        *self.unhealthy_until.lock().unwrap_or_else(PoisonError::into_inner) =
+            (!healthy).then(|| Instant::now() + UNHEALTHY_COOLDOWN);
+    }
+}
+
+/// A transport sending the requests to the first healthy endpoint of an ordered list, failing
+/// over to the next ones when it fails.
+///
+/// A failing endpoint is considered unhealthy for a while, during which it is only tried after
+/// the healthy ones.
+#[derive(Debug, Clone)]
+pub struct FailoverTransport {
+    endpoints: Arc<Vec<Endpoint>>,
+}
+
+impl FailoverTransport {
+    /// Creates a transport over the given endpoints, by order of preference.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `endpoints` is empty.
+    pub fn new(endpoints: Vec<(Url, BoxTransport)>) -> Self {
+        assert!(!endpoints.is_empty(), "at least one endpoint is required");
+
+        let endpoints = endpoints
+            .into_iter()
+            .map(|(url, transport)| Endpoint {
+                name: endpoint_name(&url),
+                transport,
+                unhealthy_until: Mutex::new(None),
+            })
+            .collect();
+
+        Self { endpoints: Arc::new(endpoints) }
+    }
+
+    async fn dispatch(self, request: RequestPacket) -> Result<ResponsePacket, TransportError> {
+        let now = Instant::now();
+        let (healthy, unhealthy): (Vec<_>, Vec<_>) =
+            self.endpoints.iter().partition(|endpoint| endpoint.is_healthy(now));
+
+        let mut error = None;
+        for endpoint in healthy.into_iter().chain(unhealthy) {
+            match endpoint.transport.clone().call(request.clone()).await {
+                Ok(response) => {
+                    endpoint.set_healthy(true);
+                    return Ok(response);
+                }
+                Err(err) => {
+                    warn!("{} failed, failing over to the next rpc: {}", endpoint.name, err);
+                    endpoint.set_healthy(false);
+                    error = Some(err);
+                }
+            }
+        }
+
+        Err(error.expect("at least one endpoint is required"))
+    }
+}
+
+impl Service<RequestPacket> for FailoverTransport {
+    type Response = ResponsePacket;
+    type Error = TransportError;
+    type Future = TransportFut<'static>;
+
+    fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, request: RequestPacket) -> Self::Future {
+        Box::pin(self.clone().dispatch(request))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicBool, Ordering};
+
+    use alloy_rpc_client::RpcClient;
+    use alloy_transport::{IntoBoxTransport, TransportErrorKind};
+    use serde_json::Value;
+
+    use super::*;
+    use crate::test_utils::{success, StubTransport};
+
+    /// Creates an endpoint answering its name, or failing while `down` is set.
+    fn endpoint(name: &'static str, down: Arc<AtomicBool>) -> (StubTransport, (Url, BoxTransport)) {
+        let stub = StubTransport::new(move |_| {
+            if down.load(Ordering::SeqCst) {
+                return Err(TransportErrorKind::custom_str("connection refused"));
+            }
+
+            Ok(success(&format!("\"{name}\"")))
+        });
+        let url = Url::parse(&format!("http://{name}.example")).unwrap();
+
+        (stub.clone(), (url, stub.into_box_transport()))
+    }
+
+    async fn chain_id(transport: &FailoverTransport) -> Value {
+        RpcClient::new(transport.clone(), true)
+            .request_noparams::<Value>("eth_chainId")
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_failover() {
+        let primary_down = Arc::new(AtomicBool::new(true));
+        let (primary, primary_endpoint) = endpoint("primary", primary_down.clone());
+        let (backup, backup_endpoint) = endpoint("backup", Arc::default());
+        let transport = FailoverTransport::new(vec![primary_endpoint, backup_endpoint]);
+
+        // The failing endpoint is skipped.
+        assert_eq!(chain_id(&transport).await, "backup");
+        assert_eq!((primary.calls(), backup.calls()), (1, 1));
+
+        // It is then only tried after the healthy ones during the cooldown.
+        primary_down.store(false, Ordering::SeqCst);
+        assert_eq!(chain_id(&transport).await, "backup");
+        assert_eq!((primary.calls(), backup.calls()), (1, 2));
+
+        // And tried first again once the cooldown is over.
+        let mut unhealthy_until = transport.endpoints[0].unhealthy_until.lock().unwrap();
+        *unhealthy_until = unhealthy_until.map(|until| until - UNHEALTHY_COOLDOWN);
+        drop(unhealthy_until);
+        assert_eq!(chain_id(&transport).await, "primary");
+        assert_eq!((primary.calls(), backup.calls()), (2, 2));
+    }
+
+    #[tokio::test]
+    async fn test_unhealthy_endpoints_are_still_tried() {
+        let backup_down = Arc::new(AtomicBool::new(true));
+        let (primary, primary_endpoint) = endpoint("primary", Arc::new(AtomicBool::new(true)));
+        let (backup, backup_endpoint) = endpoint("backup", backup_down.clone());
+        let transport = FailoverTransport::new(vec![primary_endpoint, backup_endpoint]);
+
+        let err = RpcClient::new(transport.clone(), true)
+            .request_noparams::<Value>("eth_chainId")
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("connection refused"));
+
+        // Every endpoint is unhealthy, but they are tried anyway.
+        backup_down.store(false, Ordering::SeqCst);
+        assert_eq!(chain_id(&transport).await, "backup");
+        assert_eq!((primary.calls(), backup.calls()), (2, 2));
+    }
+}
@@ -1,18 +1,201 @@
+use std::path::{Path, PathBuf};
+
 use alloy_json_rpc::RpcError;
 use alloy_provider::{Network, RootProvider};
-use alloy_rpc_client::RpcClient;
+use alloy_rpc_client::{IpcConnect, RpcClient, WsConnect};
 use alloy_transport::{
     layers::{RateLimitRetryPolicy, RetryBackoffLayer, RetryPolicy},
-    TransportError, TransportErrorKind,
+    BoxTransport, IntoBoxTransport, TransportError, TransportErrorKind, TransportResult,
 };
+use serde::{Deserialize, Serialize};
+use tracing::warn;
 use url::Url;
 
-pub fn create_provider<N: Network>(rpc_url: Url) -> RootProvider<N> {
-    let retry_layer =
-        RetryBackoffLayer::new_with_policy(3, 1000, 100, ServerErrorRetryPolicy::default());
-    let client = RpcClient::builder().layer(retry_layer).http(rpc_url);
+mod failover;
+pub use failover::FailoverTransport;
+
+mod quorum;
+pub use quorum::QuorumTransport;
+
+mod record;
+pub use record::{RecordLayer, RecordService, ReplayTransport, RpcRecorder};
+
+#[cfg(test)]
+mod test_utils;
+
+/// Returns the name of an RPC endpoint in the logs and errors: its host, as its URL may hold an
+/// API key.
+pub fn endpoint_name(url: &Url) -> String {
+    url.host_str().unwrap_or("unknown").to_string()
+}
+
+/// The retry policy of the requests sent to an RPC, on rate limiting and server errors.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RetryConfig {
+    /// The maximum number of retries of a request.
+    pub max_retries: u32,
+    /// The initial backoff between the retries, in milliseconds.
+    pub initial_backoff_ms: u64,
+    /// The compute units per second budget of the RPC, used to compute the backoff.
+    pub compute_units_per_second: u64,
+}
+
+impl Default for RetryConfig {
+    fn default() -> Self {
+        Self { max_retries: 3, initial_backoff_ms: 1000, compute_units_per_second: 100 }
+    }
+}
+
+/// Creates a provider of an RPC. `ws://` and `wss://` URLs are connected over WebSocket, and
+/// `ipc://` or `file://` URLs to the IPC socket at their path. The other URLs use HTTP.
+pub async fn create_provider<N: Network>(rpc_url: Url) -> TransportResult<RootProvider<N>> {
+    create_failover_provider(vec![rpc_url], &RetryConfig::default()).await
+}
+
+/// Creates a provider sending the requests to the first healthy RPC of `rpc_urls`, failing over
+/// to the next ones on errors.
+pub async fn create_failover_provider<N: Network>(
+    rpc_urls: Vec<Url>,
+    retry_config: &RetryConfig,
+) -> TransportResult<RootProvider<N>> {
+    let transport = failover_transport(rpc_urls, retry_config).await?;
+
+    Ok(RootProvider::new(RpcClient::new(transport, false)))
+}
+
+/// Creates a provider recording its requests and their responses into `recorder`.
+///
+/// Only the final response of each request is recorded, after the retries.
+pub async fn create_recording_provider<N: Network>(
+    rpc_urls: Vec<Url>,
+    retry_config: &RetryConfig,
+    recorder: &RpcRecorder,
+) -> TransportResult<RootProvider<N>> {
+    let client = RpcClient::builder()
+        .layer(RecordLayer::new(recorder.clone()))
+        .transport(failover_transport(rpc_urls, retry_config).await?, false);
+
+    Ok(RootProvider::new(client))
+}
+
+/// Creates a provider serving the responses recorded in a fixture file, without network access.
+pub fn create_replay_provider<N: Network>(
+    path: impl AsRef<Path>,
+) -> std::io::Result<RootProvider<N>> {
+    let client = RpcClient::new(ReplayTransport::load(path)?, true);
+
+    Ok(RootProvider::new(client))
+}
+
+/// Creates a provider comparing the proof, code and block responses of several endpoints, and
+/// returning the ones a majority of them agrees on.
+///
+/// Fails if any of the RPCs can't be connected, since the quorum would be computed over fewer
+/// endpoints than configured.
+pub async fn create_quorum_provider<N: Network>(
+    rpc_urls: Vec<Url>,
+    retry_config: &RetryConfig,
+) -> TransportResult<RootProvider<N>> {
+    if rpc_urls.is_empty() {
+        return Err(TransportErrorKind::custom_str("no rpc url given"));
+    }
+
+    let mut endpoints = Vec::with_capacity(rpc_urls.len());
+    for rpc_url in rpc_urls {
+        let transport = connect(rpc_url.clone(), retry_config).await.map_err(|err| {
+            TransportErrorKind::custom_str(&format!(
+                "failed to connect to {}: {}",
+                endpoint_name(&rpc_url),
+                err
+            ))
+        })?;
+        endpoints.push((rpc_url, transport));
+    }
+    let client = RpcClient::new(QuorumTransport::new(endpoints), false);
+
+    Ok(RootProvider::new(client))
+}
+
+/// Creates a provider able to subscribe to the new blocks, over a `ws://`, `wss://`, `ipc://`
+/// or `file://` URL.
+///
+/// The requests are not retried, since the retry layer would hide the subscriptions of the
+/// underlying transport.
+pub async fn create_subscription_provider<N: Network>(
+    rpc_url: Url,
+) -> TransportResult<RootProvider<N>> {
+    let client = match rpc_url.scheme() {
+        "ws" | "wss" => RpcClient::builder().ws(WsConnect::new(rpc_url.as_str())).await?,
+        "ipc" | "file" => {
+            RpcClient::builder().ipc(IpcConnect::new(PathBuf::from(rpc_url.path()))).await?
+        }
+        scheme => {
+            return Err(TransportErrorKind::custom_str(&format!(
+                "subscriptions are not supported over {scheme}"
+            )))
+        }
+    };
+
+    Ok(RootProvider::new(client))
+}
+
+/// Connects the transport of an RPC, retrying the failed requests.
+async fn connect(rpc_url: Url, retry_config: &RetryConfig) -> TransportResult<BoxTransport> {
+    let retry_layer = RetryBackoffLayer::new_with_policy(
+        retry_config.max_retries,
+        retry_config.initial_backoff_ms,
+        retry_config.compute_units_per_second,
+        ServerErrorRetryPolicy::default(),
+    );
+    let builder = RpcClient::builder().layer(retry_layer);
+
+    let client = match rpc_url.scheme() {
+        "ws" | "wss" => builder.ws(WsConnect::new(rpc_url.as_str())).await?,
+        "ipc" | "file" => builder.ipc(IpcConnect::new(PathBuf::from(rpc_url.path()))).await?,
+        _ => builder.http(rpc_url),
+    };
+
+    Ok(client.transport().clone())
+}
+
+/// Connects the transports of the RPCs. The RPCs failing to connect are skipped, unless all of
+/// them fail.
+async fn connect_endpoints(
+    rpc_urls: Vec<Url>,
+    retry_config: &RetryConfig,
+) -> TransportResult<Vec<(Url, BoxTransport)>> {
+    let mut endpoints = Vec::with_capacity(rpc_urls.len());
+    let mut error = None;
+
+    for rpc_url in rpc_urls {
+        match connect(rpc_url.clone(), retry_config).await {
+            Ok(transport) => endpoints.push((rpc_url, transport)),
+            Err(err) => {
+                warn!("failed to connect to {}: {}", endpoint_name(&rpc_url), err);
+                error = Some(err);
+            }
+        }
+    }
+
+    match error {
+        Some(err) if endpoints.is_empty() => Err(err),
+        _ if endpoints.is_empty() => Err(TransportErrorKind::custom_str("no rpc url given")),
+        _ => Ok(endpoints),
+    }
+}
+
+/// Creates the transport failing over the RPCs, or the transport of the RPC if there is only one.
+async fn failover_transport(
+    rpc_urls: Vec<Url>,
+    retry_config: &RetryConfig,
+) -> TransportResult<BoxTransport> {
+    let mut endpoints = connect_endpoints(rpc_urls, retry_config).await?;
+
+    if endpoints.len() == 1 {
+        return Ok(endpoints.remove(0).1);
+    }
 
-    RootProvider::new(client)
+    Ok(FailoverTransport::new(endpoints).into_box_transport())
 }
 
 #[derive(Debug, Copy, Clone, Default)]
@@ -0,0 +1,373 @@
+use std::{
+    collections::HashMap,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    task::{Context, Poll},
+    time::Duration,
+};
+
+use alloy_json_rpc::{RequestPacket, Response, ResponsePacket, ResponsePayload, SerializedRequest};
+use alloy_transport::{BoxTransport, TransportError, TransportErrorKind, TransportFut};
+use futures::future::{join_all, try_join};
+use tower::Service;
+use tracing::warn;
+use url::Url;
+
+use crate::endpoint_name;
+
+/// The methods whose responses are compared across the endpoints. The other requests are only
+/// sent to the first endpoint.
+const QUORUM_METHODS: &[&str] = &[
+    "eth_getProof",
+    "eth_getCode",
+    "eth_getStorageAt",
+    "eth_getBalance",
+    "eth_getTransactionCount",
+    "eth_getBlockByNumber",
+    "eth_getBlockByHash",
+];
+
+/// How long an endpoint is waited for by default, before being considered as failing.
+const DEFAULT_ENDPOINT_TIMEOUT: Duration = Duration::from_secs(60);
+
+#[derive(Debug)]
+struct Endpoint {
+    /// See [endpoint_name].
+    name: String,
+    transport: BoxTransport,
+    disagreements: AtomicUsize,
+}
+
+/// A transport sending the proof, code and block requests to several endpoints, and returning
+/// the response a quorum of them agrees on.
+///
+/// The endpoints whose responses disagree with the quorum are logged, and their disagreements
+/// counted. The other requests of a batch are only sent to the first endpoint.
+#[derive(Debug, Clone)]
+pub struct QuorumTransport {
+    endpoints: Arc<Vec<Endpoint>>,
+    quorum: usize,
+    timeout: Duration,
+}
+
+impl QuorumTransport {
+    /// Creates a transport requiring the agreement of a majority of the endpoints.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `endpoints` is empty.
+    pub fn new(endpoints: Vec<(Url, BoxTransport)>) -> Self {
+        assert!(!endpoints.is_empty(), "at least one endpoint is required");
+
+        let quorum = endpoints.len() / 2 + 1;
+        let endpoints = endpoints
+            .into_iter()
+            .map(|(url, transport)| Endpoint {
+                name: endpoint_name(&url),
+                transport,
+                disagreements: AtomicUsize::new(0),
+            })
+            .collect();
+
+        Self { endpoints: Arc::new(endpoints), quorum, timeout: DEFAULT_ENDPOINT_TIMEOUT }
+    }
+
+    /// Sets the number of endpoints that must agree on a response.
+    pub fn with_quorum(mut self, quorum: usize) -> Self {
+        self.quorum = quorum.clamp(1, self.endpoints.len());
+        self
+    }
+
+    /// Sets how long an endpoint is waited for, before its response is considered as failed.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Returns the number of responses of each endpoint that disagreed with the quorum.
+    pub fn disagreements(&self) -> Vec<(String, usize)> {
+        self.endpoints
+            .iter()
+            .map(|endpoint| (endpoint.name.clone(), endpoint.disagreements.load(Ordering::Relaxed)))
+            .collect()
+    }
+
+    async fn dispatch(self, request: RequestPacket) -> Result<ResponsePacket, TransportError> {
+        let (quorum_requests, other_requests): (Vec<_>, Vec<_>) = match &request {
+            RequestPacket::Single(request) => std::slice::from_ref(request),
+            RequestPacket::Batch(requests) => requests.as_slice(),
+        }
+        .iter()
+        .cloned()
+        .partition(|request| QUORUM_METHODS.contains(&request.method()));
+
+        if quorum_requests.is_empty() {
+            return self.endpoints[0].transport.clone().call(request).await;
+        }
+
+        let RequestPacket::Batch(_) = request else {
+            return Ok(ResponsePacket::Single(
+                self.vote(request, &quorum_requests).await?.remove(0),
+            ));
+        };
+
+        // Only the requests of the batch whose responses are compared are sent to every endpoint.
+        let other_responses = async {
+            if other_requests.is_empty() {
+                return Ok(Vec::new());
+            }
+
+            let packet = self.endpoints[0]
+                .transport
+                .clone()
+                .call(RequestPacket::Batch(other_requests))
+                .await?;
+
+            Ok(match packet {
+                ResponsePacket::Single(response) => vec![response],
+                ResponsePacket::Batch(responses) => responses,
+            })
+        };
+        let (mut responses, other_responses) = try_join(
+            self.vote(RequestPacket::Batch(quorum_requests.clone()), &quorum_requests),
+            other_responses,
+        )
+        .await?;
+        responses.extend(other_responses);
+
+        Ok(ResponsePacket::Batch(responses))
+    }
+
+    /// Sends the requests to every endpoint, and returns the responses a quorum of them agrees on.
+    async fn vote(
+        &self,
+        packet: RequestPacket,
+        requests: &[SerializedRequest],
+    ) -> Result<Vec<Response>, TransportError> {
+        let results = join_all(self.endpoints.iter().map(|endpoint| {
+            let response = endpoint.transport.clone().call(packet.clone());
+
+            async move {
+                tokio::time::timeout(self.timeout, response).await.unwrap_or_else(|_| {
+                    Err(TransportErrorKind::custom_str(&format!(
+                        "timed out after {:?}",
+                        self.timeout
+                    )))
+                })
+            }
+        }))
+        .await;
+
+        let mut packets = Vec::with_capacity(results.len());
+        let mut first_error = None;
+        for (endpoint, result) in self.endpoints.iter().zip(results) {
+            match result {
+                Ok(packet) => packets.push((endpoint, packet)),
+                Err(err) => {
+                    warn!("{} failed to respond: {}", endpoint.name, err);
+                    first_error.get_or_insert(err);
+                }
+            }
+        }
+
+        if packets.is_empty() {
+            return Err(first_error.expect("at least one endpoint is required"));
+        }
+
+        let mut responses = Vec::with_capacity(requests.len());
+        for request in requests {
+            let (id, method) = (request.id(), request.method());
+            let votes = packets
+                .iter()
+                .filter_map(|(endpoint, packet)| {
+                    let responses = match packet {
+                        ResponsePacket::Single(response) => std::slice::from_ref(response),
+                        ResponsePacket::Batch(responses) => responses.as_slice(),
+                    };
+                    let response = responses.iter().find(|response| &response.id == id)?;
+
+                    Some((*endpoint, response, fingerprint(method, &response.payload)))
+                })
+                .collect::<Vec<_>>();
+
+            let mut tally = HashMap::<&str, usize>::new();
+            for (_, _, fingerprint) in &votes {
+                *tally.entry(fingerprint.as_str()).or_default() += 1;
+            }
+            let (majority, count) =
+                tally.into_iter().max_by_key(|(_, count)| *count).ok_or_else(|| {
+                    TransportErrorKind::custom_str(&format!("no response to {method}"))
+                })?;
+
+            if count < self.quorum {
+                return Err(TransportErrorKind::custom_str(&format!(
+                    "no quorum on {method}: at most {count} of {} endpoints agree, {} required",
+                    self.endpoints.len(),
+                    self.quorum
+                )));
+            }
+
+            let mut response = None;
+            for (endpoint, candidate, fingerprint) in &votes {
+                if fingerprint == majority {
+                    response.get_or_insert_with(|| (*candidate).clone());
+                } else {
+                    let disagreements = endpoint.disagreements.fetch_add(1, Ordering::Relaxed) + 1;
+                    warn!(
+                        "{} disagrees with the quorum on {} ({} disagreements so far)",
+                        endpoint.name, method, disagreements
+                    );
+                }
+            }
+            responses.push(response.expect("the majority has at least one vote"));
+        }
+
+        Ok(responses)
+    }
+}
+
+/// Returns the value compared across the endpoints for a response.
+fn fingerprint(method: &str, payload: &ResponsePayload) -> String {
+    match payload {
+        ResponsePayload::Success(result) => {
+            let Ok(value) = serde_json::from_str::<serde_json::Value>(result.get()) else {
+                return result.get().to_string();
+            };
+
+            // The clients serialize different sets of fields for the blocks, so only their hash
+            // is compared.
+            if method.starts_with("eth_getBlockBy") {
+                if let Some(hash) = value.get("hash") {
+                    return hash.to_string();
+                }
+            }
+
+            value.to_string()
+        }
+        ResponsePayload::Failure(error) => format!("error {}", error.code),
+    }
+}
+
+impl Service<RequestPacket> for QuorumTransport {
+    type Response = ResponsePacket;
+    type Error = TransportError;
+    type Future = TransportFut<'static>;
+
+    fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, request: RequestPacket) -> Self::Future {
+        Box::pin(self.clone().dispatch(request))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloy_rpc_client::RpcClient;
+    use alloy_transport::IntoBoxTransport;
+    use serde_json::Value;
+
+    use super::*;
+    use crate::test_utils::{success, StubTransport};
+
+    /// Creates a transport over endpoints answering `eth_getCode` with the given codes, and
+    /// `eth_chainId` if they are the first endpoint.
+    fn quorum(codes: &[&'static str]) -> (QuorumTransport, Vec<StubTransport>) {
+        let stubs = codes
+            .iter()
+            .enumerate()
+            .map(|(i, &code)| {
+                StubTransport::new(move |request| match request.method() {
+                    "eth_getCode" => Ok(success(&format!("\"{code}\""))),
+                    "eth_chainId" if i == 0 => Ok(success("\"0x1\"")),
+                    method => Err(TransportErrorKind::custom_str(&format!("unexpected {method}"))),
+                })
+            })
+            .collect::<Vec<_>>();
+        let endpoints = stubs
+            .iter()
+            .enumerate()
+            .map(|(i, stub)| {
+                let url = Url::parse(&format!("http://rpc{i}.example")).unwrap();
+                (url, stub.clone().into_box_transport())
+            })
+            .collect();
+
+        (QuorumTransport::new(endpoints), stubs)
+    }
+
+    async fn get_code(transport: &QuorumTransport) -> Result<Value, String> {
+        RpcClient::new(transport.clone(), true)
+            .request::<_, Value>(
+                "eth_getCode",
+                ("0x0000000000000000000000000000000000000001", "0x1"),
+            )
+            .await
+            .map_err(|err| err.to_string())
+    }
+
+    #[tokio::test]
+    async fn test_majority() {
+        let (transport, _) = quorum(&["0x01", "0x02", "0x01"]);
+
+        assert_eq!(get_code(&transport).await.unwrap(), "0x01");
+        assert_eq!(
+            transport.disagreements(),
+            vec![
+                ("rpc0.example".to_string(), 0),
+                ("rpc1.example".to_string(), 1),
+                ("rpc2.example".to_string(), 0)
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_no_quorum() {
+        let (transport, _) = quorum(&["0x01", "0x02", "0x03"]);
+        assert!(get_code(&transport).await.unwrap_err().contains("no quorum on eth_getCode"));
+
+        let (transport, _) = quorum(&["0x01", "0x01", "0x02"]);
+        let transport = transport.with_quorum(3);
+        assert!(get_code(&transport).await.unwrap_err().contains("no quorum on eth_getCode"));
+    }
+
+    #[tokio::test]
+    async fn test_batch_is_split_by_method() {
+        let (transport, stubs) = quorum(&["0x01", "0x01", "0x01"]);
+        let client = RpcClient::new(transport, true);
+
+        let mut batch = client.new_batch();
+        let code = batch
+            .add_call::<_, Value>(
+                "eth_getCode",
+                &("0x0000000000000000000000000000000000000001", "0x1"),
+            )
+            .unwrap();
+        let chain_id = batch.add_call::<_, Value>("eth_chainId", &()).unwrap();
+        batch.send().await.unwrap();
+
+        assert_eq!(code.await.unwrap(), "0x01");
+        assert_eq!(chain_id.await.unwrap(), "0x1");
+        // The first endpoint got the compared requests and the others in separate batches.
+        assert_eq!(stubs.iter().map(StubTransport::calls).collect::<Vec<_>>(), vec![2, 1, 1]);
+    }
+
+    #[test]
+    fn test_fingerprint() {
+        assert_eq!(
+            fingerprint("eth_getProof", &success(r#"{"nonce":"0x1","balance":"0x0"}"#)),
+            fingerprint("eth_getProof", &success(r#"{ "balance": "0x0", "nonce": "0x1" }"#))
+        );
+        assert_eq!(
+            fingerprint("eth_getBlockByNumber", &success(r#"{"hash":"0x01","size":"0x10"}"#)),
+            fingerprint("eth_getBlockByNumber", &success(r#"{"hash":"0x01"}"#))
+        );
+        assert_ne!(
+            fingerprint("eth_getCode", &success(r#""0x00""#)),
+            fingerprint("eth_getCode", &success(r#""0x01""#))
+        );
+    }
+}
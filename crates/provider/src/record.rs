@@ -0,0 +1,325 @@
+use std::{
+    collections::HashMap,
+    fs::File,
+    io::{BufReader, BufWriter},
+    path::Path,
+    sync::{Arc, Mutex, PoisonError},
+    task::{Context, Poll},
+};
+
+use alloy_json_rpc::{
+    ErrorPayload, Id, RequestPacket, Response, ResponsePacket, ResponsePayload, SerializedRequest,
+};
+use alloy_transport::{Transport, TransportError, TransportErrorKind, TransportFut};
+use serde::{Deserialize, Serialize};
+use serde_json::value::RawValue;
+use tower::{Layer, Service};
+
+/// A JSON-RPC request and the response it got, stored in the fixture files.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Interaction {
+    method: String,
+    params: Option<Box<RawValue>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    result: Option<Box<RawValue>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    error: Option<ErrorPayload>,
+}
+
+impl Interaction {
+    /// The key matching a replayed request to the recorded one, regardless of its id.
+    fn key(&self) -> (String, Option<String>) {
+        (self.method.clone(), self.params.as_ref().map(|params| params.get().to_string()))
+    }
+}
+
+fn request_key(request: &SerializedRequest) -> (String, Option<String>) {
+    (request.method().to_string(), request.params().map(|params| params.get().to_string()))
+}
+
+/// The JSON-RPC interactions recorded by the [RecordLayer] of one or several providers.
+#[derive(Debug, Clone, Default)]
+pub struct RpcRecorder {
+    interactions: Arc<Mutex<Vec<Interaction>>>,
+}
+
+impl RpcRecorder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the number of recorded interactions.
+    pub fn len(&self) -> usize {
+        self.interactions.lock().unwrap_or_else(PoisonError::into_inner).len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Saves the recorded interactions to a fixture file, to be served by a [ReplayTransport].
+    pub fn save(&self, path: impl AsRef<Path>) -> std::io::Result<()> {
+        let path = path.as_ref();
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+
+        let interactions = self.interactions.lock().unwrap_or_else(PoisonError::into_inner);
+        serde_json::to_writer_pretty(BufWriter::new(File::create(path)?), &*interactions)?;
+
+        Ok(())
+    }
+
+    fn record(
+        &self,
+        requests: Vec<(Id, String, Option<Box<RawValue>>)>,
+        response: &ResponsePacket,
+    ) {
+        let responses = match response {
+            ResponsePacket::Single(response) => std::slice::from_ref(response),
+            ResponsePacket::Batch(responses) => responses.as_slice(),
+        };
+
+        let mut interactions = self.interactions.lock().unwrap_or_else(PoisonError::into_inner);
+        for (id, method, params) in requests {
+            let Some(response) = responses.iter().find(|response| response.id == id) else {
+                continue;
+            };
+
+            let (result, error) = match &response.payload {
+                ResponsePayload::Success(result) => (Some(result.clone()), None),
+                ResponsePayload::Failure(error) => (None, Some(error.clone())),
+            };
+            interactions.push(Interaction { method, params, result, error });
+        }
+    }
+}
+
+/// A [Layer] recording the requests sent through the transport, and their responses.
+#[derive(Debug, Clone)]
+pub struct RecordLayer {
+    recorder: RpcRecorder,
+}
+
+impl RecordLayer {
+    pub fn new(recorder: RpcRecorder) -> Self {
+        Self { recorder }
+    }
+}
+
+impl<S> Layer<S> for RecordLayer {
+    type Service = RecordService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        RecordService { inner, recorder: self.recorder.clone() }
+    }
+}
+
+/// The transport service created by the [RecordLayer].
+#[derive(Debug, Clone)]
+pub struct RecordService<S> {
+    inner: S,
+    recorder: RpcRecorder,
+}
+
+impl<S> Service<RequestPacket> for RecordService<S>
+where
+    S: Transport + Clone,
+{
+    type Response = ResponsePacket;
+    type Error = TransportError;
+    type Future = TransportFut<'static>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, request: RequestPacket) -> Self::Future {
+        let requests = match &request {
+            RequestPacket::Single(request) => std::slice::from_ref(request),
+            RequestPacket::Batch(requests) => requests.as_slice(),
+        }
+        .iter()
+        .map(|request| {
+            (
+                request.id().clone(),
+                request.method().to_string(),
+                request.params().map(|params| params.to_owned()),
+            )
+        })
+        .collect::<Vec<_>>();
+
+        let recorder = self.recorder.clone();
+        let response = self.inner.call(request);
+
+        Box::pin(async move {
+            let response = response.await?;
+            recorder.record(requests, &response);
+
+            Ok(response)
+        })
+    }
+}
+
+/// A transport serving the responses recorded in a fixture file, without any network access.
+///
+/// The requests are matched on their method and parameters. When the same request has been
+/// recorded several times, the last response is served.
+#[derive(Debug, Clone)]
+pub struct ReplayTransport {
+    interactions: Arc<HashMap<(String, Option<String>), Interaction>>,
+}
+
+impl ReplayTransport {
+    /// Loads the interactions saved by a [RpcRecorder].
+    pub fn load(path: impl AsRef<Path>) -> std::io::Result<Self> {
+        let interactions: Vec<Interaction> =
+            serde_json::from_reader(BufReader::new(File::open(path)?))?;
+
+        Ok(Self {
+            interactions: Arc::new(
+                interactions
+                    .into_iter()
+                    .map(|interaction| (interaction.key(), interaction))
+                    .collect(),
+            ),
+        })
+    }
+
+    fn respond(&self, request: &SerializedRequest) -> Result<Response, TransportError> {
+        let interaction = self.interactions.get(&request_key(request)).ok_or_else(|| {
+            TransportErrorKind::custom_str(&format!(
+                "no recorded response for {} with params {}",
+                request.method(),
+                request.params().map_or("[]", |params| params.get())
+            ))
+        })?;
+
+        let payload = match (&interaction.result, &interaction.error) {
+            (_, Some(error)) => ResponsePayload::Failure(error.clone()),
+            (Some(result), None) => ResponsePayload::Success(result.clone()),
+            (None, None) => {
+                return Err(TransportErrorKind::custom_str(&format!(
+                    "invalid recorded response for {}",
+                    request.method()
+                )))
+            }
+        };
+
+        Ok(Response { id: request.id().clone(), payload })
+    }
+}
+
+impl Service<RequestPacket> for ReplayTransport {
+    type Response = ResponsePacket;
+    type Error = TransportError;
+    type Future = TransportFut<'static>;
+
+    fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, request: RequestPacket) -> Self::Future {
+        let response = match &request {
+            RequestPacket::Single(request) => self.respond(request).map(ResponsePacket::Single),
+            RequestPacket::Batch(requests) => requests
+                .iter()
+                .map(|request| self.respond(request))
+                .collect::<Result<Vec<_>, _>>()
+                .map(ResponsePacket::Batch),
+        };
+
+        Box::pin(async move { response })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloy_rpc_client::RpcClient;
+    use serde_json::{json, Value};
+
+    use super::*;
+    use crate::test_utils::{success, StubTransport};
+
+    /// Sends a single request and a batch, returning their results.
+    async fn send_requests(client: &RpcClient) -> Vec<Result<Value, String>> {
+        let single = client
+            .request::<_, Value>(
+                "eth_getCode",
+                ("0x0000000000000000000000000000000000000001", "0x1"),
+            )
+            .await
+            .map_err(|err| err.to_string());
+
+        let mut batch = client.new_batch();
+        let proof = batch
+            .add_call::<_, Value>(
+                "eth_getProof",
+                &("0x0000000000000000000000000000000000000002", Vec::<String>::new(), "0x1"),
+            )
+            .unwrap();
+        let balance = batch
+            .add_call::<_, Value>(
+                "eth_getBalance",
+                &("0x0000000000000000000000000000000000000003", "0x1"),
+            )
+            .unwrap();
+        batch.send().await.unwrap();
+
+        vec![
+            single,
+            proof.await.map_err(|err| err.to_string()),
+            balance.await.map_err(|err| err.to_string()),
+        ]
+    }
+
+    #[tokio::test]
+    async fn test_record_and_replay() {
+        // Echo the requests, except for the balances which fail.
+        let transport = StubTransport::new(|request| {
+            if request.method() == "eth_getBalance" {
+                return Ok(ResponsePayload::Failure(ErrorPayload {
+                    code: -32000,
+                    message: "header not found".into(),
+                    data: None,
+                }));
+            }
+
+            let params: Value = serde_json::from_str(request.params().unwrap().get()).unwrap();
+            Ok(success(&json!({ "method": request.method(), "params": params }).to_string()))
+        });
+
+        let recorder = RpcRecorder::new();
+        let client = RpcClient::builder()
+            .layer(RecordLayer::new(recorder.clone()))
+            .transport(transport.clone(), true);
+        let recorded = send_requests(&client).await;
+
+        assert_eq!(transport.calls(), 2);
+        assert_eq!(recorder.len(), 3);
+        assert_eq!(
+            recorded[0].as_ref().unwrap()["params"][0],
+            "0x0000000000000000000000000000000000000001"
+        );
+        assert_eq!(recorded[1].as_ref().unwrap()["method"], "eth_getProof");
+        assert!(recorded[2].as_ref().unwrap_err().contains("header not found"));
+
+        let path = std::env::temp_dir().join(format!("rpc-record-{}.json", std::process::id()));
+        recorder.save(&path).unwrap();
+
+        let client = RpcClient::new(ReplayTransport::load(&path).unwrap(), true);
+        assert_eq!(send_requests(&client).await, recorded);
+
+        // The requests that weren't recorded fail.
+        let err = client
+            .request::<_, Value>(
+                "eth_getCode",
+                ("0x0000000000000000000000000000000000000004", "0x1"),
+            )
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("no recorded response for eth_getCode"));
+
+        std::fs::remove_file(path).unwrap();
+    }
+}
@@ -0,0 +1,78 @@
+use std::{
+    fmt,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    task::{Context, Poll},
+};
+
+use alloy_json_rpc::{RequestPacket, Response, ResponsePacket, ResponsePayload, SerializedRequest};
+use alloy_transport::{TransportError, TransportFut};
+use serde_json::value::RawValue;
+use tower::Service;
+
+type Handler =
+    dyn Fn(&SerializedRequest) -> Result<ResponsePayload, TransportError> + Send + Sync + 'static;
+
+/// A transport answering the requests with a handler, and counting the packets it receives.
+#[derive(Clone)]
+pub(crate) struct StubTransport {
+    handler: Arc<Handler>,
+    calls: Arc<AtomicUsize>,
+}
+
+impl StubTransport {
+    pub(crate) fn new(
+        handler: impl Fn(&SerializedRequest) -> Result<ResponsePayload, TransportError>
+            + Send
+            + Sync
+            + 'static,
+    ) -> Self {
+        Self { handler: Arc::new(handler), calls: Arc::default() }
+    }
+
+    /// Returns the number of packets received, a batch counting as one.
+    pub(crate) fn calls(&self) -> usize {
+        self.calls.load(Ordering::SeqCst)
+    }
+}
+
+impl fmt::Debug for StubTransport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("StubTransport").field("calls", &self.calls()).finish()
+    }
+}
+
+impl Service<RequestPacket> for StubTransport {
+    type Response = ResponsePacket;
+    type Error = TransportError;
+    type Future = TransportFut<'static>;
+
+    fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, request: RequestPacket) -> Self::Future {
+        self.calls.fetch_add(1, Ordering::SeqCst);
+
+        let respond = |request: &SerializedRequest| {
+            (self.handler)(request).map(|payload| Response { id: request.id().clone(), payload })
+        };
+        let response = match &request {
+            RequestPacket::Single(request) => respond(request).map(ResponsePacket::Single),
+            RequestPacket::Batch(requests) => requests
+                .iter()
+                .map(respond)
+                .collect::<Result<Vec<_>, _>>()
+                .map(ResponsePacket::Batch),
+        };
+
+        Box::pin(async move { response })
+    }
+}
+
+/// Returns a successful response with the given JSON result.
+pub(crate) fn success(json: &str) -> ResponsePayload {
+    ResponsePayload::Success(RawValue::from_string(json.to_string()).unwrap())
+}
@@ -5,22 +5,77 @@ use std::{
 };
 
 use alloy_consensus::{BlockHeader, Header};
-use alloy_primitives::{map::HashMap, U256};
+use alloy_eips::BlockId;
+use alloy_primitives::{keccak256, map::HashMap, Bytes, U256};
 use alloy_provider::{
+    ext::DebugApi,
     network::{primitives::HeaderResponse, BlockResponse},
     Network, Provider,
 };
+use alloy_rpc_client::BatchRequest;
+use alloy_rpc_types::EIP1186AccountProofResponse;
+use alloy_rpc_types_trace::geth::{
+    GethDebugTracingOptions, PreStateConfig, PreStateFrame, TraceResult,
+};
+use alloy_transport::TransportError;
 use async_trait::async_trait;
+use futures::{future::try_join, stream, StreamExt, TryStreamExt};
 use mpt::EthereumState;
 use primitives::account_proof::eip1186_proof_to_account_proof;
 use reth_storage_errors::{db::DatabaseError, provider::ProviderError};
+use reth_trie::AccountProof;
 use revm_database::BundleState;
 use revm_database_interface::DatabaseRef;
 use revm_primitives::{Address, B256, KECCAK_EMPTY};
 use revm_state::{AccountInfo, Bytecode};
-use tracing::debug;
+use tracing::{debug, warn};
+
+use crate::{
+    cache::RpcCache,
+    error::{ProviderRole, RpcDbError},
+    RpcDb,
+};
+
+/// The default maximum number of concurrent requests of the bulk fetches.
+pub const DEFAULT_MAX_CONCURRENT_REQUESTS: usize = 32;
+
+/// The configuration of the bulk fetches done once the block has been executed, i.e. the account
+/// proofs and the ancestor headers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FetchConfig {
+    /// The maximum number of concurrent requests.
+    pub max_concurrent_requests: usize,
+    /// The number of accounts whose proofs are fetched in a single JSON-RPC batch. Batching is
+    /// disabled if lower than 2.
+    pub batch_size: usize,
+    /// Whether to prefetch the state accessed by the block with the `prestateTracer` before
+    /// executing it.
+    pub prefetch: bool,
+    /// The number of times an invalid proof or code is fetched again from a provider, before
+    /// falling back to the next one.
+    pub invalid_proof_retries: usize,
+}
+
+impl Default for FetchConfig {
+    fn default() -> Self {
+        Self {
+            max_concurrent_requests: DEFAULT_MAX_CONCURRENT_REQUESTS,
+            batch_size: 1,
+            prefetch: false,
+            invalid_proof_retries: 1,
+        }
+    }
+}
 
-use crate::{error::RpcDbError, RpcDb};
+/// The keys whose proofs must be fetched for an account.
+#[derive(Debug)]
+struct ProofRequest {
+    address: Address,
+    /// The keys accessed or modified during the execution.
+    keys: Vec<B256>,
+    /// The keys modified during the execution.
+    modified_keys: Vec<B256>,
+}
 
 /// A database that fetches data from a [Provider] over a [Transport].
 #[derive(Debug, Clone)]
@@ -29,14 +84,39 @@ pub struct BasicRpcDb<P, N> {
     pub provider: P,
     /// The block to fetch data from.
     pub block_number: u64,
+    /// The hash of `block_number`, the data is fetched by hash if known so that it all comes from
+    /// the same fork.
+    pub block_hash: Option<B256>,
+    /// The host of `provider`, reported in the errors blaming it.
+    pub provider_host: String,
+    /// The provider the invalid proofs and code are fetched again from, once the retries on
+    /// `provider` are exhausted, and its host.
+    pub fallback_provider: Option<(P, String)>,
+    /// The block to fetch the post-state proofs from, after all the blocks have been executed.
+    pub post_state_block_number: u64,
+    /// The hash of `post_state_block_number`, the post-state proofs are fetched by hash if known.
+    pub post_state_block_hash: Option<B256>,
     ///The state root to fetch data from.
     pub state_root: B256,
+    /// The state root of `post_state_block_number`, the post-state proofs are verified against it
+    /// if known.
+    pub post_state_root: Option<B256>,
     /// The cached accounts.
     pub accounts: Arc<RwLock<HashMap<Address, AccountInfo>>>,
     /// The cached storage values.
     pub storage: Arc<RwLock<HashMap<Address, HashMap<U256, U256>>>>,
     /// The oldest block whose header/hash has been requested.
     pub oldest_ancestor: Arc<RwLock<u64>>,
+    /// The hashes of the ancestors resolved by following the parent hashes from the pinned blocks.
+    pub ancestor_hashes: Arc<RwLock<BTreeMap<u64, B256>>>,
+    /// The accounts loaded by the prefetch, moved to `accounts` once accessed.
+    pub prefetched_accounts: Arc<RwLock<HashMap<Address, AccountInfo>>>,
+    /// The storage values loaded by the prefetch, moved to `storage` once accessed.
+    pub prefetched_storage: Arc<RwLock<HashMap<Address, HashMap<U256, U256>>>>,
+    /// The configuration of the bulk fetches.
+    pub fetch_config: FetchConfig,
+    /// The cache of the headers, bytecodes and proofs shared with the other blocks.
+    pub cache: Option<RpcCache>,
 
     phantom: PhantomData<N>,
 }
@@ -46,46 +126,198 @@ impl<P: Provider<N> + Clone, N: Network> BasicRpcDb<P, N> {
     pub fn new(provider: P, block_number: u64, state_root: B256) -> Self {
         Self {
             provider,
+            provider_host: "unknown".to_string(),
+            fallback_provider: None,
             block_number,
+            block_hash: None,
+            post_state_block_number: block_number + 1,
+            post_state_block_hash: None,
             state_root,
+            post_state_root: None,
             accounts: Arc::new(RwLock::new(HashMap::with_hasher(Default::default()))),
             storage: Arc::new(RwLock::new(HashMap::with_hasher(Default::default()))),
             oldest_ancestor: Arc::new(RwLock::new(block_number)),
+            ancestor_hashes: Arc::new(RwLock::new(BTreeMap::new())),
+            prefetched_accounts: Arc::new(RwLock::new(HashMap::with_hasher(Default::default()))),
+            prefetched_storage: Arc::new(RwLock::new(HashMap::with_hasher(Default::default()))),
+            fetch_config: FetchConfig::default(),
+            cache: None,
             phantom: PhantomData,
         }
     }
 
+    /// Sets the block to fetch the post-state proofs from, when executing several consecutive
+    /// blocks on top of `block_number`.
+    pub fn with_post_state_block_number(mut self, post_state_block_number: u64) -> Self {
+        self.post_state_block_number = post_state_block_number;
+        self
+    }
+
+    /// Pins the queries to the block with the given hash, as per EIP-1898, instead of its number.
+    pub fn with_block_hash(mut self, block_hash: B256) -> Self {
+        self.block_hash = Some(block_hash);
+        self
+    }
+
+    /// Pins the post-state queries to the block with the given hash.
+    pub fn with_post_state_block_hash(mut self, post_state_block_hash: B256) -> Self {
+        self.post_state_block_hash = Some(post_state_block_hash);
+        self
+    }
+
+    /// Sets the state root of the post-state block, to verify the post-state proofs against it.
+    pub fn with_post_state_root(mut self, post_state_root: B256) -> Self {
+        self.post_state_root = Some(post_state_root);
+        self
+    }
+
+    /// Sets the host of the provider, reported in the errors blaming it.
+    pub fn with_provider_host(mut self, provider_host: String) -> Self {
+        self.provider_host = provider_host;
+        self
+    }
+
+    /// Sets the provider the invalid proofs and code are fetched again from, and its host.
+    pub fn with_fallback_provider(mut self, fallback_provider: P, host: String) -> Self {
+        self.fallback_provider = Some((fallback_provider, host));
+        self
+    }
+
+    /// Sets the configuration of the bulk fetches.
+    pub fn with_fetch_config(mut self, fetch_config: FetchConfig) -> Self {
+        self.fetch_config = fetch_config;
+        self
+    }
+
+    /// Sets the cache shared with the other blocks.
+    pub fn with_cache(mut self, cache: RpcCache) -> Self {
+        self.cache = Some(cache);
+        self
+    }
+
+    /// Prefetch the state accessed by the block `block_number`, whose hash is given, using the
+    /// `prestateTracer` of `debug_traceBlockByHash`.
+    ///
+    /// The prestate of a block reflects the changes made by the previous blocks, including the
+    /// ones made outside of their transactions (e.g. withdrawals), so a block must be prefetched
+    /// only once the previous blocks of the range have been executed. The accounts and slots
+    /// already fetched are then kept, as well as the first occurrence of the others. The
+    /// accesses not covered by the traces are still fetched lazily. Returns the number of
+    /// prefetched accounts.
+    pub async fn prefetch_prestate(
+        &self,
+        block_number: u64,
+        block_hash: B256,
+    ) -> Result<usize, RpcDbError> {
+        tracing::info!("[{}] prefetching prestate", block_number);
+
+        let traces = self
+            .provider
+            .debug_trace_block_by_hash(
+                block_hash,
+                GethDebugTracingOptions::prestate_tracer(PreStateConfig::default()),
+            )
+            .await
+            .map_err(|e| RpcDbError::PrefetchError(block_number, e.to_string()))?;
+
+        let mut accounts = HashMap::<Address, AccountInfo>::default();
+        let mut storage = HashMap::<Address, HashMap<U256, U256>>::default();
+        for trace in traces {
+            let frame = match trace {
+                TraceResult::Success { result, .. } => result
+                    .try_into_pre_state_frame()
+                    .map_err(|e| RpcDbError::PrefetchError(block_number, e.to_string()))?,
+                TraceResult::Error { error, tx_hash } => {
+                    // A missing trace hides the changes of the transaction from the subsequent
+                    // prestates, which therefore can't be trusted.
+                    return Err(RpcDbError::PrefetchError(
+                        block_number,
+                        format!("failed to trace transaction {tx_hash:?}: {error}"),
+                    ));
+                }
+            };
+
+            merge_prestate(frame, &mut accounts, &mut storage)
+                .map_err(|e| RpcDbError::PrefetchError(block_number, e))?;
+        }
+
+        let fetched_accounts = self.accounts.read().map_err(|_| RpcDbError::Poisoned)?;
+        let fetched_storage = self.storage.read().map_err(|_| RpcDbError::Poisoned)?;
+        let mut prefetched_accounts =
+            self.prefetched_accounts.write().map_err(|_| RpcDbError::Poisoned)?;
+        let mut prefetched_storage =
+            self.prefetched_storage.write().map_err(|_| RpcDbError::Poisoned)?;
+
+        let mut prefetched = 0;
+        for (address, account) in accounts {
+            if !fetched_accounts.contains_key(&address) &&
+                !prefetched_accounts.contains_key(&address)
+            {
+                prefetched_accounts.insert(address, account);
+                prefetched += 1;
+            }
+        }
+        for (address, slots) in storage {
+            let fetched_slots = fetched_storage.get(&address);
+            let prefetched_slots = prefetched_storage.entry(address).or_default();
+            for (index, value) in slots {
+                if !fetched_slots.is_some_and(|fetched_slots| fetched_slots.contains_key(&index)) {
+                    prefetched_slots.entry(index).or_insert(value);
+                }
+            }
+        }
+
+        Ok(prefetched)
+    }
+
     /// Fetch the [AccountInfo] for an [Address].
     pub async fn fetch_account_info(&self, address: Address) -> Result<AccountInfo, RpcDbError> {
+        let prefetched =
+            self.prefetched_accounts.write().map_err(|_| RpcDbError::Poisoned)?.remove(&address);
+        if let Some(account_info) = prefetched {
+            self.accounts
+                .write()
+                .map_err(|_| RpcDbError::Poisoned)?
+                .insert(address, account_info.clone());
+
+            return Ok(account_info);
+        }
+
         debug!("fetching account info for address: {}", address);
 
         // Fetch the proof for the account.
         let proof = self
-            .provider
-            .get_proof(address, vec![])
-            .number(self.block_number)
-            .await
-            .map_err(|e| RpcDbError::GetProofError(address, e.to_string()))?;
-
-        // Fetch the code of the account.
-        let code = self
-            .provider
-            .get_code_at(address)
-            .number(self.block_number)
-            .await
-            .map_err(|e| RpcDbError::GetCodeError(address, e.to_string()))?;
-
-        // Construct the account info & write it to the log.
-        let bytecode = Bytecode::new_raw(code);
+            .fetch_verified_proof(address, vec![], self.block_number, Some(self.state_root))
+            .await?;
+        let account = proof.info.unwrap_or_default();
 
         // Normalize code_hash for REVM compatibility:
         // RPC response for getProof method for non-existing (unused) EOAs may contain B256::ZERO
         // for code_hash, but REVM expects KECCAK_EMPTY
-        let code_hash = if proof.code_hash == B256::ZERO { KECCAK_EMPTY } else { proof.code_hash };
+        let code_hash =
+            account.bytecode_hash.filter(|hash| !hash.is_zero()).unwrap_or(KECCAK_EMPTY);
+
+        // Fetch the code of the account, unless it has none.
+        let cached_code = self.cache.as_ref().and_then(|cache| cache.bytecode(code_hash));
+        let code = if code_hash == KECCAK_EMPTY {
+            Bytes::new()
+        } else if let Some(code) = cached_code {
+            code
+        } else {
+            let code = self.fetch_verified_code(address, code_hash).await?;
+            if let Some(cache) = &self.cache {
+                cache.insert_bytecode(code_hash, code.clone());
+            }
+
+            code
+        };
+
+        // Construct the account info & write it to the log.
+        let bytecode = Bytecode::new_raw(code);
 
         let account_info = AccountInfo {
-            nonce: proof.nonce,
-            balance: proof.balance,
+            nonce: account.nonce,
+            balance: account.balance,
             code_hash,
             code: Some(bytecode.clone()),
         };
@@ -105,13 +337,26 @@ impl<P: Provider<N> + Clone, N: Network> BasicRpcDb<P, N> {
         address: Address,
         index: U256,
     ) -> Result<U256, RpcDbError> {
+        let prefetched = self
+            .prefetched_storage
+            .write()
+            .map_err(|_| RpcDbError::Poisoned)?
+            .get_mut(&address)
+            .and_then(|storage| storage.remove(&index));
+        if let Some(value) = prefetched {
+            let mut storage_values = self.storage.write().map_err(|_| RpcDbError::Poisoned)?;
+            storage_values.entry(address).or_default().insert(index, value);
+
+            return Ok(value);
+        }
+
         debug!("fetching storage value at address: {}, index: {}", address, index);
 
         // Fetch the storage value.
         let value = self
             .provider
             .get_storage_at(address, index)
-            .number(self.block_number)
+            .block_id(self.block_id(self.block_number))
             .await
             .map_err(|e| RpcDbError::GetStorageError(address, index, e.to_string()))?;
 
@@ -124,26 +369,372 @@ impl<P: Provider<N> + Clone, N: Network> BasicRpcDb<P, N> {
     }
 
     /// Fetch the block hash for a block number.
+    ///
+    /// When the queries are pinned to block hashes, the hash is resolved by following the parent
+    /// hashes from the closest pinned or already resolved descendant, so that it is from the
+    /// executed fork. It is fetched by number otherwise.
     pub async fn fetch_block_hash(&self, number: u64) -> Result<B256, RpcDbError> {
         debug!("fetching block hash for block number: {}", number);
 
-        // Fetch the block.
-        let block = self
-            .provider
-            .get_block_by_number(number.into())
-            .await
-            .map_err(|e| RpcDbError::GetBlockError(number, e.to_string()))?;
+        let hash = match self.ancestor_hash(number).await? {
+            Some(hash) => hash,
+            None => {
+                // Fetch the block.
+                let block = self
+                    .provider
+                    .get_block_by_number(number.into())
+                    .await
+                    .map_err(|e| RpcDbError::GetBlockError(number, e.to_string()))?;
 
-        // Record the block hash to the state.
-        let block = block.ok_or(RpcDbError::BlockNotFound(number))?;
-        let hash = block.header().hash();
+                block.ok_or(RpcDbError::BlockNotFound(number))?.header().hash()
+            }
+        };
 
+        // Record the block hash to the state.
         let mut oldest_ancestor = self.oldest_ancestor.write().map_err(|_| RpcDbError::Poisoned)?;
         *oldest_ancestor = number.min(*oldest_ancestor);
 
         Ok(hash)
     }
 
+    /// Resolves the hash of the block `number` from the hash-linked chain of its pinned
+    /// descendants, if any.
+    async fn ancestor_hash(&self, number: u64) -> Result<Option<B256>, RpcDbError> {
+        let descendant = {
+            let mut ancestor_hashes =
+                self.ancestor_hashes.write().map_err(|_| RpcDbError::Poisoned)?;
+            ancestor_hashes.extend(self.block_hash.map(|hash| (self.block_number, hash)));
+            ancestor_hashes.extend(
+                self.post_state_block_hash.map(|hash| (self.post_state_block_number, hash)),
+            );
+            ancestor_hashes.range(number..).next().map(|(&number, &hash)| (number, hash))
+        };
+        let Some((mut height, mut hash)) = descendant else {
+            return Ok(None);
+        };
+
+        while height > number {
+            let header = match self.cache.as_ref().and_then(|cache| cache.header(hash)) {
+                Some(header) => header,
+                None => self.fetch_header(BlockId::hash(hash)).await?,
+            };
+            height -= 1;
+            hash = header.parent_hash;
+            self.ancestor_hashes.write().map_err(|_| RpcDbError::Poisoned)?.insert(height, hash);
+        }
+
+        Ok(Some(hash))
+    }
+
+    /// Fetch the proofs of the accounts before and after the execution, sending the requests in a
+    /// single JSON-RPC batch if enabled.
+    async fn fetch_transition_proofs(
+        &self,
+        requests: &[ProofRequest],
+    ) -> Result<Vec<(AccountProof, AccountProof)>, RpcDbError> {
+        if self.fetch_config.batch_size > 1 && requests.len() > 1 {
+            match self.fetch_transition_proofs_batch(requests).await {
+                Ok(proofs) => return Ok(proofs),
+                Err(err) => {
+                    // Not all the providers support batching, fall back to individual requests.
+                    warn!("batched eth_getProof failed, falling back to single requests: {err}");
+                }
+            }
+        }
+
+        let mut proofs = Vec::with_capacity(requests.len());
+        for request in requests {
+            proofs.push(
+                try_join(
+                    self.fetch_verified_proof(
+                        request.address,
+                        request.keys.clone(),
+                        self.block_number,
+                        Some(self.state_root),
+                    ),
+                    self.fetch_verified_proof(
+                        request.address,
+                        request.modified_keys.clone(),
+                        self.post_state_block_number,
+                        self.post_state_root,
+                    ),
+                )
+                .await?,
+            );
+        }
+
+        Ok(proofs)
+    }
+
+    async fn fetch_transition_proofs_batch(
+        &self,
+        requests: &[ProofRequest],
+    ) -> Result<Vec<(AccountProof, AccountProof)>, RpcDbError> {
+        // The proofs before and after the execution are looked up separately in the cache, so
+        // that only the missing ones are batched.
+        let mut before = requests
+            .iter()
+            .map(|request| self.cached_proof(Some(self.state_root), request.address, &request.keys))
+            .collect::<Vec<_>>();
+        let mut after = requests
+            .iter()
+            .map(|request| {
+                self.cached_proof(self.post_state_root, request.address, &request.modified_keys)
+            })
+            .collect::<Vec<_>>();
+
+        let pending = before
+            .iter_mut()
+            .zip(requests)
+            .map(|(proof, request)| {
+                (proof, request.address, &request.keys, self.block_number, Some(self.state_root))
+            })
+            .chain(after.iter_mut().zip(requests).map(|(proof, request)| {
+                (
+                    proof,
+                    request.address,
+                    &request.modified_keys,
+                    self.post_state_block_number,
+                    self.post_state_root,
+                )
+            }))
+            .filter(|(proof, ..)| proof.is_none())
+            .collect::<Vec<_>>();
+
+        if !pending.is_empty() {
+            let mut batch = BatchRequest::new(self.provider.client());
+
+            let waiters = pending
+                .iter()
+                .map(|(_, address, keys, block_number, _)| {
+                    batch.add_call::<_, EIP1186AccountProofResponse>(
+                        "eth_getProof",
+                        &(*address, keys.to_vec(), self.block_id(*block_number)),
+                    )
+                })
+                .collect::<Result<Vec<_>, TransportError>>()?;
+
+            batch.send().await?;
+
+            for (waiter, (proof, _, keys, block_number, state_root)) in
+                waiters.into_iter().zip(pending)
+            {
+                let fetched = eip1186_proof_to_account_proof(waiter.await?);
+                *proof = Some(self.check_proof(fetched, keys, block_number, state_root).await?);
+            }
+        }
+
+        Ok(before
+            .into_iter()
+            .zip(after)
+            .map(|(before, after)| {
+                (
+                    before.expect("the missing proofs are fetched"),
+                    after.expect("the missing proofs are fetched"),
+                )
+            })
+            .collect())
+    }
+
+    /// Returns the cached proof of an account at a state root, if known.
+    fn cached_proof(
+        &self,
+        state_root: Option<B256>,
+        address: Address,
+        keys: &[B256],
+    ) -> Option<AccountProof> {
+        self.cache.as_ref()?.proof(state_root?, address, keys)
+    }
+
+    fn cache_proof(&self, state_root: B256, proof: &AccountProof) {
+        if let Some(cache) = &self.cache {
+            cache.insert_proof(state_root, proof.clone());
+        }
+    }
+
+    /// The providers the data is fetched from, in order, with their host.
+    fn providers(&self) -> impl Iterator<Item = (ProviderRole, &str, &P)> {
+        std::iter::once((ProviderRole::Primary, self.provider_host.as_str(), &self.provider)).chain(
+            self.fallback_provider
+                .iter()
+                .map(|(provider, host)| (ProviderRole::Fallback, host.as_str(), provider)),
+        )
+    }
+
+    /// Fetch the proof of an account at a block, and verify it against the state root of the
+    /// block if known.
+    ///
+    /// Invalid proofs are fetched again, from the fallback provider once the retries on the
+    /// primary one are exhausted.
+    async fn fetch_verified_proof(
+        &self,
+        address: Address,
+        keys: Vec<B256>,
+        block_number: u64,
+        state_root: Option<B256>,
+    ) -> Result<AccountProof, RpcDbError> {
+        if let Some(proof) = self.cached_proof(state_root, address, &keys) {
+            return Ok(proof);
+        }
+
+        let mut error = None;
+
+        for (role, host, provider) in self.providers() {
+            for _ in 0..=self.fetch_config.invalid_proof_retries {
+                let proof = provider
+                    .get_proof(address, keys.clone())
+                    .block_id(self.block_id(block_number))
+                    .await
+                    .map_err(|e| RpcDbError::GetProofError(address, e.to_string()))?;
+                let proof = eip1186_proof_to_account_proof(proof);
+
+                let Some(state_root) = state_root else {
+                    return Ok(proof);
+                };
+                match verify_account_proof(&proof, state_root) {
+                    Ok(()) => {
+                        self.cache_proof(state_root, &proof);
+                        return Ok(proof);
+                    }
+                    Err(reason) => {
+                        warn!(
+                            "invalid proof of {} at block {} from the {} provider {}: {}",
+                            address, block_number, role, host, reason
+                        );
+                        error = Some(RpcDbError::InvalidProof(
+                            address,
+                            block_number,
+                            role,
+                            host.to_string(),
+                            reason,
+                        ));
+                    }
+                }
+            }
+        }
+
+        Err(error.expect("the primary provider is always queried"))
+    }
+
+    /// Verify a proof fetched in a batch, fetching it again if invalid.
+    async fn check_proof(
+        &self,
+        proof: AccountProof,
+        keys: &[B256],
+        block_number: u64,
+        state_root: Option<B256>,
+    ) -> Result<AccountProof, RpcDbError> {
+        let Some(state_root) = state_root else {
+            return Ok(proof);
+        };
+
+        match verify_account_proof(&proof, state_root) {
+            Ok(()) => {
+                self.cache_proof(state_root, &proof);
+                Ok(proof)
+            }
+            Err(reason) => {
+                warn!(
+                    "invalid proof of {} at block {} from the batch: {}",
+                    proof.address, block_number, reason
+                );
+                self.fetch_verified_proof(
+                    proof.address,
+                    keys.to_vec(),
+                    block_number,
+                    Some(state_root),
+                )
+                .await
+            }
+        }
+    }
+
+    /// Fetch the code of an account, and verify it against its proven code hash.
+    async fn fetch_verified_code(
+        &self,
+        address: Address,
+        code_hash: B256,
+    ) -> Result<Bytes, RpcDbError> {
+        let mut error = None;
+
+        for (role, host, provider) in self.providers() {
+            for _ in 0..=self.fetch_config.invalid_proof_retries {
+                let code = provider
+                    .get_code_at(address)
+                    .block_id(self.block_id(self.block_number))
+                    .await
+                    .map_err(|e| RpcDbError::GetCodeError(address, e.to_string()))?;
+
+                if keccak256(&code) == code_hash {
+                    return Ok(code);
+                }
+
+                warn!(
+                    "invalid code of {} at block {} from the {} provider {}",
+                    address, self.block_number, role, host
+                );
+                error = Some(RpcDbError::InvalidCode(
+                    address,
+                    self.block_number,
+                    role,
+                    host.to_string(),
+                ));
+            }
+        }
+
+        Err(error.expect("the primary provider is always queried"))
+    }
+
+    /// Returns the id of the block the queries at `block_number` are made against: its hash if
+    /// known, its number otherwise.
+    fn block_id(&self, block_number: u64) -> BlockId {
+        let block_hash = if block_number == self.block_number {
+            self.block_hash
+        } else if block_number == self.post_state_block_number {
+            self.post_state_block_hash
+        } else {
+            None
+        };
+
+        block_hash.map_or(BlockId::number(block_number), BlockId::hash)
+    }
+
+    /// Fetch the header of a block.
+    async fn fetch_header(&self, block: BlockId) -> Result<Header, RpcDbError> {
+        let block =
+            self.provider.get_block(block).await?.ok_or(RpcDbError::HeaderNotFound(block))?;
+
+        let header = Header {
+            parent_hash: block.header().parent_hash(),
+            ommers_hash: block.header().ommers_hash(),
+            beneficiary: block.header().beneficiary(),
+            state_root: block.header().state_root(),
+            transactions_root: block.header().transactions_root(),
+            receipts_root: block.header().receipts_root(),
+            logs_bloom: block.header().logs_bloom(),
+            difficulty: block.header().difficulty(),
+            number: block.header().number(),
+            gas_limit: block.header().gas_limit(),
+            gas_used: block.header().gas_used(),
+            timestamp: block.header().timestamp(),
+            extra_data: block.header().extra_data().clone(),
+            mix_hash: block.header().mix_hash().unwrap_or_default(),
+            nonce: block.header().nonce().unwrap_or_default(),
+            base_fee_per_gas: block.header().base_fee_per_gas(),
+            withdrawals_root: block.header().withdrawals_root(),
+            blob_gas_used: block.header().blob_gas_used(),
+            excess_blob_gas: block.header().excess_blob_gas(),
+            parent_beacon_block_root: block.header().parent_beacon_block_root(),
+            requests_hash: block.header().requests_hash(),
+        };
+
+        if let Some(cache) = &self.cache {
+            cache.insert_header(block.header().hash(), header.clone());
+        }
+
+        Ok(header)
+    }
+
     /// Gets all the state keys used. The client uses this to read the actual state data from tries.
     pub fn get_state_requests(&self) -> HashMap<Address, Vec<U256>> {
         let accounts = self.accounts.read().unwrap();
@@ -204,6 +795,58 @@ impl<P: Provider<N> + Clone, N: Network> DatabaseRef for BasicRpcDb<P, N> {
     }
 }
 
+/// Merges the prestate of a transaction into the prefetched accounts and storage values, keeping
+/// the values already present since they were accessed earlier.
+///
+/// The tracer omits the fields of an account which are zero or empty.
+fn merge_prestate(
+    frame: PreStateFrame,
+    accounts: &mut HashMap<Address, AccountInfo>,
+    storage: &mut HashMap<Address, HashMap<U256, U256>>,
+) -> Result<(), String> {
+    let PreStateFrame::Default(prestate) = frame else {
+        return Err("unexpected diff mode prestate".to_string());
+    };
+
+    for (address, account) in prestate.0 {
+        accounts.entry(address).or_insert_with(|| {
+            let code = account.code.unwrap_or_default();
+            let code_hash = if code.is_empty() { KECCAK_EMPTY } else { keccak256(&code) };
+
+            AccountInfo {
+                nonce: account.nonce.unwrap_or_default(),
+                balance: account.balance.unwrap_or_default(),
+                code_hash,
+                code: Some(Bytecode::new_raw(code)),
+            }
+        });
+
+        let account_storage = storage.entry(address).or_default();
+        for (key, value) in account.storage {
+            account_storage
+                .entry(U256::from_be_bytes(key.0))
+                .or_insert(U256::from_be_bytes(value.0));
+        }
+    }
+
+    Ok(())
+}
+
+/// Verify an account proof against a state root.
+fn verify_account_proof(proof: &AccountProof, state_root: B256) -> Result<(), String> {
+    // Some providers report a zero code hash for the accounts without code.
+    if proof.info.is_some_and(|info| info.bytecode_hash == Some(B256::ZERO)) {
+        let mut proof = proof.clone();
+        if let Some(info) = proof.info.as_mut() {
+            info.bytecode_hash = Some(KECCAK_EMPTY);
+        }
+
+        return proof.verify(state_root).map_err(|e| e.to_string());
+    }
+
+    proof.verify(state_root).map_err(|e| e.to_string())
+}
+
 #[async_trait]
 impl<P, N> RpcDb<N> for BasicRpcDb<P, N>
 where
@@ -215,39 +858,39 @@ where
 
         // For every account we touched, fetch the storage proofs for all the slots we touched.
         tracing::info!("fetching storage proofs");
-        let mut before_storage_proofs = Vec::new();
-        let mut after_storage_proofs = Vec::new();
-
-        for (address, used_keys) in state_requests.iter() {
-            let modified_keys = bundle_state
-                .state
-                .get(address)
-                .map(|account| {
-                    account.storage.keys().map(|key| B256::from(*key)).collect::<BTreeSet<_>>()
-                })
-                .unwrap_or_default()
-                .into_iter()
-                .collect::<Vec<_>>();
+        let requests = state_requests
+            .iter()
+            .map(|(address, used_keys)| {
+                let modified_keys = bundle_state
+                    .state
+                    .get(address)
+                    .map(|account| {
+                        account.storage.keys().map(|key| B256::from(*key)).collect::<BTreeSet<_>>()
+                    })
+                    .unwrap_or_default()
+                    .into_iter()
+                    .collect::<Vec<_>>();
 
-            let keys = used_keys
-                .iter()
-                .map(|key| B256::from(*key))
-                .chain(modified_keys.clone().into_iter())
-                .collect::<BTreeSet<_>>()
-                .into_iter()
-                .collect::<Vec<_>>();
-
-            let storage_proof =
-                self.provider.get_proof(*address, keys.clone()).number(self.block_number).await?;
-            before_storage_proofs.push(eip1186_proof_to_account_proof(storage_proof));
-
-            let storage_proof = self
-                .provider
-                .get_proof(*address, modified_keys)
-                .number(self.block_number + 1)
-                .await?;
-            after_storage_proofs.push(eip1186_proof_to_account_proof(storage_proof));
-        }
+                let keys = used_keys
+                    .iter()
+                    .map(|key| B256::from(*key))
+                    .chain(modified_keys.clone().into_iter())
+                    .collect::<BTreeSet<_>>()
+                    .into_iter()
+                    .collect::<Vec<_>>();
+
+                ProofRequest { address: *address, keys, modified_keys }
+            })
+            .collect::<Vec<_>>();
+
+        let proofs = stream::iter(requests.chunks(self.fetch_config.batch_size.max(1)))
+            .map(|requests| self.fetch_transition_proofs(requests))
+            .buffered(self.fetch_config.max_concurrent_requests.max(1))
+            .try_collect::<Vec<_>>()
+            .await?;
+
+        let (before_storage_proofs, after_storage_proofs): (Vec<_>, Vec<_>) =
+            proofs.into_iter().flatten().unzip();
 
         let state = EthereumState::from_transition_proofs(
             self.state_root,
@@ -272,40 +915,332 @@ where
 
     async fn ancestor_headers(&self) -> Result<Vec<Header>, RpcDbError> {
         let oldest_ancestor = *self.oldest_ancestor.read().unwrap();
-        let mut ancestor_headers = vec![];
         tracing::info!("fetching {} ancestor headers", (self.block_number + 1) - oldest_ancestor);
-        for height in (oldest_ancestor..=(self.block_number)).rev() {
-            let block = self
-                .provider
-                .get_block_by_number(height.into())
-                .await?
-                .ok_or(RpcDbError::BlockNotFound(height))?;
-
-            ancestor_headers.push(Header {
-                parent_hash: block.header().parent_hash(),
-                ommers_hash: block.header().ommers_hash(),
-                beneficiary: block.header().beneficiary(),
-                state_root: block.header().state_root(),
-                transactions_root: block.header().transactions_root(),
-                receipts_root: block.header().receipts_root(),
-                logs_bloom: block.header().logs_bloom(),
-                difficulty: block.header().difficulty(),
-                number: block.header().number(),
-                gas_limit: block.header().gas_limit(),
-                gas_used: block.header().gas_used(),
-                timestamp: block.header().timestamp(),
-                extra_data: block.header().extra_data().clone(),
-                mix_hash: block.header().mix_hash().unwrap_or_default(),
-                nonce: block.header().nonce().unwrap_or_default(),
-                base_fee_per_gas: block.header().base_fee_per_gas(),
-                withdrawals_root: block.header().withdrawals_root(),
-                blob_gas_used: block.header().blob_gas_used(),
-                excess_blob_gas: block.header().excess_blob_gas(),
-                parent_beacon_block_root: block.header().parent_beacon_block_root(),
-                requests_hash: block.header().requests_hash(),
-            });
+
+        // The most recent header is always fetched, the older ones are then looked up in the cache
+        // by following the parent hashes, which keeps them consistent across reorgs.
+        let mut ancestor_headers = vec![self.fetch_header(self.block_id(self.block_number)).await?];
+        if let Some(cache) = &self.cache {
+            loop {
+                let last = ancestor_headers.last().expect("the most recent header is fetched");
+                if last.number <= oldest_ancestor {
+                    break;
+                }
+                let Some(header) = cache.header(last.parent_hash) else {
+                    break;
+                };
+                ancestor_headers.push(header);
+            }
+        }
+
+        let oldest_cached =
+            ancestor_headers.last().expect("the most recent header is fetched").number;
+        let fetched = stream::iter((oldest_ancestor..oldest_cached).rev())
+            .map(|height| self.fetch_header(BlockId::number(height)))
+            .buffered(self.fetch_config.max_concurrent_requests.max(1))
+            .try_collect::<Vec<_>>()
+            .await?;
+        ancestor_headers.extend(fetched);
+
+        // The headers fetched by number are from another fork if a reorg happened meanwhile, they
+        // are then fetched again by the hash of their child.
+        for i in 1..ancestor_headers.len() {
+            let parent_hash = ancestor_headers[i - 1].parent_hash;
+            if ancestor_headers[i].hash_slow() != parent_hash {
+                warn!("ancestor header {} is from another fork", ancestor_headers[i].number);
+                ancestor_headers[i] = self.fetch_header(BlockId::hash(parent_hash)).await?;
+            }
         }
 
         Ok(ancestor_headers)
     }
+
+    async fn prefetch(&self, block_number: u64, block_hash: B256) -> Result<usize, RpcDbError> {
+        self.prefetch_prestate(block_number, block_hash).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        sync::atomic::{AtomicUsize, Ordering},
+        task::{Context, Poll},
+    };
+
+    use alloy_json_rpc::{
+        RequestPacket, Response, ResponsePacket, ResponsePayload, SerializedRequest,
+    };
+    use alloy_provider::{network::Ethereum, RootProvider};
+    use alloy_rpc_client::RpcClient;
+    use alloy_transport::{TransportErrorKind, TransportFut};
+    use reth_trie::EMPTY_ROOT_HASH;
+    use serde_json::value::RawValue;
+    use tower::Service;
+
+    use super::*;
+
+    /// A transport rejecting the batches unless `batching` is set, and answering every
+    /// `eth_getProof` with the proof of an account absent from an empty state, tampered with the
+    /// given balance if not zero, and every `debug_traceBlockByHash` with a single transaction of
+    /// the given prestate.
+    #[derive(Debug, Clone, Default)]
+    struct ProofTransport {
+        balance: U256,
+        prestate: serde_json::Value,
+        batching: bool,
+        batches: Arc<AtomicUsize>,
+        requests: Arc<AtomicUsize>,
+    }
+
+    impl ProofTransport {
+        fn respond(&self, request: &SerializedRequest) -> Response {
+            self.requests.fetch_add(1, Ordering::SeqCst);
+
+            let result = match request.method() {
+                "debug_traceBlockByHash" => serde_json::json!([
+                    { "txHash": B256::ZERO, "result": self.prestate }
+                ]),
+                method => {
+                    assert_eq!(method, "eth_getProof");
+
+                    let params: Vec<serde_json::Value> =
+                        serde_json::from_str(request.params().unwrap().get()).unwrap();
+                    serde_json::json!({
+                        "address": params[0],
+                        "balance": self.balance,
+                        "codeHash": B256::ZERO,
+                        "nonce": "0x0",
+                        "storageHash": B256::ZERO,
+                        "accountProof": [],
+                        "storageProof": [],
+                    })
+                }
+            };
+
+            Response {
+                id: request.id().clone(),
+                payload: ResponsePayload::Success(
+                    RawValue::from_string(result.to_string()).unwrap(),
+                ),
+            }
+        }
+    }
+
+    impl Service<RequestPacket> for ProofTransport {
+        type Response = ResponsePacket;
+        type Error = TransportError;
+        type Future = TransportFut<'static>;
+
+        fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+            Poll::Ready(Ok(()))
+        }
+
+        fn call(&mut self, request: RequestPacket) -> Self::Future {
+            let response = match request {
+                RequestPacket::Batch(requests) => {
+                    self.batches.fetch_add(1, Ordering::SeqCst);
+                    if self.batching {
+                        Ok(ResponsePacket::Batch(
+                            requests.iter().map(|request| self.respond(request)).collect(),
+                        ))
+                    } else {
+                        Err(TransportErrorKind::custom_str("batch requests are not supported"))
+                    }
+                }
+                RequestPacket::Single(request) => {
+                    Ok(ResponsePacket::Single(self.respond(&request)))
+                }
+            };
+
+            Box::pin(async move { response })
+        }
+    }
+
+    fn provider(transport: &ProofTransport) -> RootProvider<Ethereum> {
+        RootProvider::new(RpcClient::new(transport.clone(), true))
+    }
+
+    #[test]
+    fn test_merge_prestate() {
+        let frames: [PreStateFrame; 2] = [
+            serde_json::from_str(
+                r#"{
+                    "0x0000000000000000000000000000000000000001": {
+                        "balance": "0x10",
+                        "nonce": 1,
+                        "code": "0x6000",
+                        "storage": {
+                            "0x0000000000000000000000000000000000000000000000000000000000000001": "0x0000000000000000000000000000000000000000000000000000000000000002"
+                        }
+                    },
+                    "0x0000000000000000000000000000000000000002": {}
+                }"#,
+            )
+            .unwrap(),
+            serde_json::from_str(
+                r#"{
+                    "0x0000000000000000000000000000000000000001": {
+                        "balance": "0x20",
+                        "nonce": 2,
+                        "storage": {
+                            "0x0000000000000000000000000000000000000000000000000000000000000001": "0x0000000000000000000000000000000000000000000000000000000000000003",
+                            "0x0000000000000000000000000000000000000000000000000000000000000002": "0x0000000000000000000000000000000000000000000000000000000000000004"
+                        }
+                    }
+                }"#,
+            )
+            .unwrap(),
+        ];
+
+        let mut accounts = HashMap::default();
+        let mut storage = HashMap::default();
+        for frame in frames {
+            merge_prestate(frame, &mut accounts, &mut storage).unwrap();
+        }
+
+        // The first occurrence of the account and of the slot wins.
+        let account = &accounts[&Address::with_last_byte(1)];
+        assert_eq!((account.nonce, account.balance), (1, U256::from(0x10)));
+        assert_eq!(account.code_hash, keccak256([0x60, 0x00]));
+        assert_eq!(
+            storage[&Address::with_last_byte(1)],
+            HashMap::from_iter([(U256::from(1), U256::from(2)), (U256::from(2), U256::from(4))])
+        );
+
+        // The omitted fields are zero.
+        let account = &accounts[&Address::with_last_byte(2)];
+        assert_eq!((account.nonce, account.balance), (0, U256::ZERO));
+        assert_eq!(account.code_hash, KECCAK_EMPTY);
+        assert!(storage[&Address::with_last_byte(2)].is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_prefetch_keeps_the_fetched_accounts() {
+        // The prestate of the second block holds the balance of the withdrawal recipient of the
+        // first one after the withdrawal.
+        let recipient = Address::with_last_byte(1);
+        let transport = ProofTransport {
+            prestate: serde_json::json!({
+                "0x0000000000000000000000000000000000000001": { "balance": "0x2" },
+                "0x0000000000000000000000000000000000000002": { "balance": "0x3" },
+            }),
+            ..Default::default()
+        };
+        let db = BasicRpcDb::<_, Ethereum>::new(provider(&transport), 1, EMPTY_ROOT_HASH);
+
+        // The recipient is fetched by the withdrawal of the first block.
+        assert_eq!(db.fetch_account_info(recipient).await.unwrap().balance, U256::ZERO);
+
+        assert_eq!(db.prefetch_prestate(3, B256::repeat_byte(3)).await.unwrap(), 1);
+        assert_eq!(db.fetch_account_info(recipient).await.unwrap().balance, U256::ZERO);
+        let requests = transport.requests.load(Ordering::SeqCst);
+        assert_eq!(
+            db.fetch_account_info(Address::with_last_byte(2)).await.unwrap().balance,
+            U256::from(3)
+        );
+        assert_eq!(transport.requests.load(Ordering::SeqCst), requests);
+    }
+
+    #[tokio::test]
+    async fn test_batch_falls_back_to_single_requests() {
+        let transport = ProofTransport::default();
+        let db = BasicRpcDb::<_, Ethereum>::new(provider(&transport), 1, EMPTY_ROOT_HASH)
+            .with_fetch_config(FetchConfig { batch_size: 2, ..Default::default() });
+
+        let requests = [Address::with_last_byte(1), Address::with_last_byte(2)]
+            .map(|address| ProofRequest { address, keys: vec![], modified_keys: vec![] });
+        let proofs = db.fetch_transition_proofs(&requests).await.unwrap();
+
+        assert_eq!(
+            proofs.iter().map(|(before, _)| before.address).collect::<Vec<_>>(),
+            vec![Address::with_last_byte(1), Address::with_last_byte(2)]
+        );
+        assert!(proofs.iter().all(|(before, after)| before.info.is_none() && after.info.is_none()));
+        assert_eq!(transport.batches.load(Ordering::SeqCst), 1);
+        // The proofs before and after the execution of both accounts.
+        assert_eq!(transport.requests.load(Ordering::SeqCst), 4);
+    }
+
+    #[tokio::test]
+    async fn test_batch_skips_the_cached_proofs() {
+        let transport = ProofTransport { batching: true, ..Default::default() };
+        let cache = RpcCache::new(std::num::NonZeroUsize::new(8).unwrap());
+        let db = BasicRpcDb::<_, Ethereum>::new(provider(&transport), 1, EMPTY_ROOT_HASH)
+            .with_fetch_config(FetchConfig { batch_size: 2, ..Default::default() })
+            .with_cache(cache);
+
+        let requests = [Address::with_last_byte(1), Address::with_last_byte(2)]
+            .map(|address| ProofRequest { address, keys: vec![], modified_keys: vec![] });
+        db.fetch_transition_proofs(&requests).await.unwrap();
+        assert_eq!(transport.batches.load(Ordering::SeqCst), 1);
+        assert_eq!(transport.requests.load(Ordering::SeqCst), 4);
+
+        // The proofs before the execution are cached, while the ones after it can't be verified
+        // without the post-state root, so only the latter are batched again.
+        let proofs = db.fetch_transition_proofs(&requests).await.unwrap();
+        assert_eq!(
+            proofs
+                .iter()
+                .map(|(before, after)| (before.address, after.address))
+                .collect::<Vec<_>>(),
+            requests.map(|request| (request.address, request.address))
+        );
+        assert_eq!(transport.batches.load(Ordering::SeqCst), 2);
+        assert_eq!(transport.requests.load(Ordering::SeqCst), 6);
+    }
+
+    #[tokio::test]
+    async fn test_tampered_proof_is_rejected() {
+        let transport = ProofTransport { balance: U256::from(1), ..Default::default() };
+        let fallback_transport = ProofTransport { balance: U256::from(2), ..Default::default() };
+        let db = BasicRpcDb::<_, Ethereum>::new(provider(&transport), 1, EMPTY_ROOT_HASH)
+            .with_provider_host("primary.example".to_string())
+            .with_fallback_provider(provider(&fallback_transport), "fallback.example".to_string());
+
+        let err = db
+            .fetch_verified_proof(Address::with_last_byte(1), vec![], 1, Some(EMPTY_ROOT_HASH))
+            .await
+            .unwrap_err();
+
+        // The proof is fetched again once from each provider before blaming the last one.
+        assert!(matches!(
+            err,
+            RpcDbError::InvalidProof(address, 1, ProviderRole::Fallback, host, _)
+                if address == Address::with_last_byte(1) && host == "fallback.example"
+        ));
+        assert_eq!(transport.requests.load(Ordering::SeqCst), 2);
+        assert_eq!(fallback_transport.requests.load(Ordering::SeqCst), 2);
+
+        // The proofs are not verified if the state root is unknown.
+        let proof =
+            db.fetch_verified_proof(Address::with_last_byte(1), vec![], 2, None).await.unwrap();
+        assert_eq!(proof.info.unwrap().balance, U256::from(1));
+    }
+
+    #[tokio::test]
+    async fn test_block_hash_follows_the_parent_hashes() {
+        let mut headers = vec![Header { number: 7, ..Default::default() }];
+        for number in 8..=10 {
+            let parent_hash = headers.last().unwrap().hash_slow();
+            headers.push(Header { number, parent_hash, ..Default::default() });
+        }
+        let cache = RpcCache::new(std::num::NonZeroUsize::new(8).unwrap());
+        for header in &headers {
+            cache.insert_header(header.hash_slow(), header.clone());
+        }
+
+        // Any request would fail the test, the transport only serving proofs.
+        let transport = ProofTransport::default();
+        let db = BasicRpcDb::<_, Ethereum>::new(provider(&transport), 9, EMPTY_ROOT_HASH)
+            .with_block_hash(headers[2].hash_slow())
+            .with_post_state_block_number(10)
+            .with_post_state_block_hash(headers[3].hash_slow())
+            .with_cache(cache);
+
+        assert_eq!(db.fetch_block_hash(7).await.unwrap(), headers[0].hash_slow());
+        assert_eq!(db.fetch_block_hash(8).await.unwrap(), headers[1].hash_slow());
+        assert_eq!(db.fetch_block_hash(9).await.unwrap(), headers[2].hash_slow());
+        assert_eq!(*db.oldest_ancestor.read().unwrap(), 7);
+        assert_eq!(transport.requests.load(Ordering::SeqCst), 0);
+    }
 }
@@ -0,0 +1,174 @@
+use std::{
+    num::NonZeroUsize,
+    sync::{Arc, Mutex, MutexGuard, PoisonError},
+};
+
+use alloy_consensus::Header;
+use alloy_primitives::{Address, Bytes, B256};
+use lru::LruCache;
+use reth_trie::AccountProof;
+
+/// The default maximum number of entries of each kind kept by the [RpcCache].
+pub const DEFAULT_RPC_CACHE_SIZE: usize = 4096;
+
+/// A bounded cache of the data fetched by the [BasicRpcDb](crate::BasicRpcDb), shared across the
+/// executions of consecutive blocks.
+///
+/// The entries are keyed by hash so that they remain valid across reorgs: the headers by block
+/// hash, the bytecodes by code hash, and the account proofs by the state root they are proven
+/// against. The post-state proofs of a block are thereby reused as the pre-state proofs of the
+/// next one.
+///
+/// The trie nodes are not cached by node hash: `eth_getProof` always returns the whole path of an
+/// account, so the nodes shared by the proofs of different state roots can't be requested less.
+#[derive(Debug, Clone)]
+pub struct RpcCache {
+    inner: Arc<Mutex<RpcCacheInner>>,
+}
+
+#[derive(Debug)]
+struct RpcCacheInner {
+    headers: LruCache<B256, Header>,
+    bytecodes: LruCache<B256, Bytes>,
+    proofs: LruCache<(B256, Address), AccountProof>,
+}
+
+impl RpcCache {
+    /// Creates a cache keeping at most `capacity` entries of each kind.
+    pub fn new(capacity: NonZeroUsize) -> Self {
+        Self {
+            inner: Arc::new(Mutex::new(RpcCacheInner {
+                headers: LruCache::new(capacity),
+                bytecodes: LruCache::new(capacity),
+                proofs: LruCache::new(capacity),
+            })),
+        }
+    }
+
+    /// Returns the header of the block with the given hash.
+    pub fn header(&self, hash: B256) -> Option<Header> {
+        self.lock().headers.get(&hash).cloned()
+    }
+
+    pub fn insert_header(&self, hash: B256, header: Header) {
+        self.lock().headers.put(hash, header);
+    }
+
+    /// Returns the bytecode with the given code hash.
+    pub fn bytecode(&self, code_hash: B256) -> Option<Bytes> {
+        self.lock().bytecodes.get(&code_hash).cloned()
+    }
+
+    pub fn insert_bytecode(&self, code_hash: B256, code: Bytes) {
+        self.lock().bytecodes.put(code_hash, code);
+    }
+
+    /// Returns the proof of an account against `state_root`, if the cached proof covers all the
+    /// storage `keys`.
+    pub fn proof(&self, state_root: B256, address: Address, keys: &[B256]) -> Option<AccountProof> {
+        let mut inner = self.lock();
+        let cached = inner.proofs.get(&(state_root, address))?;
+
+        let storage_proofs = keys
+            .iter()
+            .map(|key| {
+                cached
+                    .storage_proofs
+                    .iter()
+                    .find(|storage_proof| storage_proof.key == *key)
+                    .cloned()
+            })
+            .collect::<Option<Vec<_>>>()?;
+
+        Some(AccountProof {
+            address,
+            info: cached.info,
+            proof: cached.proof.clone(),
+            storage_root: cached.storage_root,
+            storage_proofs,
+        })
+    }
+
+    /// Inserts a proof verified against `state_root`, merging its storage proofs with the cached
+    /// ones.
+    pub fn insert_proof(&self, state_root: B256, proof: AccountProof) {
+        let mut inner = self.lock();
+
+        match inner.proofs.get_mut(&(state_root, proof.address)) {
+            Some(cached) => {
+                for storage_proof in proof.storage_proofs {
+                    if !cached.storage_proofs.iter().any(|cached| cached.key == storage_proof.key) {
+                        cached.storage_proofs.push(storage_proof);
+                    }
+                }
+            }
+            None => {
+                inner.proofs.put((state_root, proof.address), proof);
+            }
+        }
+    }
+
+    fn lock(&self) -> MutexGuard<'_, RpcCacheInner> {
+        // The cache is never left in an inconsistent state, so a poisoned lock is still usable.
+        self.inner.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloy_primitives::U256;
+    use reth_trie::StorageProof;
+
+    use super::*;
+
+    fn account_proof(address: Address, keys: &[B256]) -> AccountProof {
+        AccountProof {
+            address,
+            info: None,
+            proof: vec![],
+            storage_root: B256::ZERO,
+            storage_proofs: keys.iter().map(|key| StorageProof::new(*key)).collect(),
+        }
+    }
+
+    fn keys(proof: &AccountProof) -> Vec<B256> {
+        proof.storage_proofs.iter().map(|storage_proof| storage_proof.key).collect()
+    }
+
+    #[test]
+    fn test_proof_subset_lookup() {
+        let cache = RpcCache::new(NonZeroUsize::new(8).unwrap());
+        let (root, address) = (B256::with_last_byte(1), Address::with_last_byte(1));
+        let (k1, k2, k3) =
+            (B256::with_last_byte(1), B256::with_last_byte(2), B256::with_last_byte(3));
+
+        cache.insert_proof(root, account_proof(address, &[k1, k2]));
+
+        // Any subset of the cached keys is served, in the requested order.
+        assert_eq!(keys(&cache.proof(root, address, &[]).unwrap()), vec![]);
+        assert_eq!(keys(&cache.proof(root, address, &[k2, k1]).unwrap()), vec![k2, k1]);
+        assert!(cache.proof(root, address, &[k1, k3]).is_none());
+
+        // The proofs are only served for the state root they were verified against.
+        assert!(cache.proof(B256::with_last_byte(2), address, &[k1]).is_none());
+        assert!(cache.proof(root, Address::with_last_byte(2), &[]).is_none());
+    }
+
+    #[test]
+    fn test_insert_proof_merges_storage_proofs() {
+        let cache = RpcCache::new(NonZeroUsize::new(8).unwrap());
+        let (root, address) = (B256::with_last_byte(1), Address::with_last_byte(1));
+        let (k1, k2, k3) =
+            (B256::with_last_byte(1), B256::with_last_byte(2), B256::with_last_byte(3));
+
+        cache.insert_proof(root, account_proof(address, &[k1, k2]));
+        let mut proof = account_proof(address, &[k2, k3]);
+        proof.storage_proofs[0].value = U256::from(1);
+        cache.insert_proof(root, proof);
+
+        let cached = cache.proof(root, address, &[k1, k2, k3]).unwrap();
+        assert_eq!(keys(&cached), vec![k1, k2, k3]);
+        // The storage proofs already cached are kept.
+        assert!(cached.storage_proofs[1].value.is_zero());
+    }
+}
@@ -1,3 +1,6 @@
+use std::fmt;
+
+use alloy_eips::BlockId;
 use alloy_transport::TransportError;
 use mpt::FromProofError;
 use revm_primitives::{Address, U256};
@@ -15,12 +18,40 @@ pub enum RpcDbError {
     GetCodeError(Address, String),
     #[error("failed to fetch storage at {0}, index {1}: {2}")]
     GetStorageError(Address, U256, String),
+    #[error("invalid proof of {0} at block {1} served by the {2} provider {3}: {4}")]
+    InvalidProof(Address, u64, ProviderRole, String, String),
+    #[error("invalid code of {0} at block {1} served by the {2} provider {3}: hash mismatch")]
+    InvalidCode(Address, u64, ProviderRole, String),
     #[error("failed to fetch block {0}: {1}")]
     GetBlockError(u64, String),
     #[error("failed to find block {0}")]
     BlockNotFound(u64),
+    #[error("failed to find the header of block {0}")]
+    HeaderNotFound(BlockId),
+    #[error("the data of block {0} is from another fork")]
+    OtherFork(u64),
+    #[error("failed to prefetch the prestate of block {0}: {1}")]
+    PrefetchError(u64, String),
     #[error("failed to find trie node preimage")]
     PreimageNotFound,
     #[error("poisoned lock")]
     Poisoned,
 }
+
+/// The provider serving a response to the [RpcDb].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProviderRole {
+    /// The provider the data is fetched from.
+    Primary,
+    /// The provider invalid responses of the primary provider are fetched again from.
+    Fallback,
+}
+
+impl fmt::Display for ProviderRole {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Primary => write!(f, "primary"),
+            Self::Fallback => write!(f, "fallback"),
+        }
+    }
+}
@@ -6,7 +6,7 @@ use alloy_provider::{ext::DebugApi, Network, Provider};
 use alloy_rlp::Decodable;
 use alloy_trie::TrieAccount;
 use async_trait::async_trait;
-use mpt::EthereumState;
+use mpt::{EthereumState, FromProofError};
 use reth_storage_errors::ProviderError;
 use revm_database::{BundleState, DatabaseRef};
 use revm_primitives::{keccak256, ruint::aliases::U256, StorageKey, StorageValue};
@@ -33,7 +33,7 @@ impl<P: Provider<N> + Clone, N: Network> ExecutionWitnessRpcDb<P, N> {
     pub async fn new(provider: P, block_number: u64, state_root: B256) -> Result<Self, RpcDbError> {
         let execution_witness = provider.debug_execution_witness((block_number + 1).into()).await?;
 
-        let state = EthereumState::from_execution_witness(&execution_witness, state_root);
+        let state = EthereumState::from_execution_witness(&execution_witness, state_root)?;
 
         let codes = execution_witness
             .codes
@@ -44,9 +44,9 @@ impl<P: Provider<N> + Clone, N: Network> ExecutionWitnessRpcDb<P, N> {
         let ancestor_headers = execution_witness
             .headers
             .iter()
-            .map(|encoded| Header::decode(&mut encoded.as_ref()).unwrap())
-            .map(|h| (h.number, h))
-            .collect();
+            .map(|encoded| Header::decode(&mut encoded.as_ref()).map(|h| (h.number, h)))
+            .collect::<Result<_, _>>()
+            .map_err(|err| FromProofError::InvalidExecutionWitness(err.to_string()))?;
 
         let db = Self { provider, state, codes, ancestor_headers, phantom: PhantomData };
 
@@ -96,12 +96,14 @@ impl<P: Provider<N> + Clone, N: Network> DatabaseRef for ExecutionWitnessRpcDb<P
         let slot = B256::from(index);
         let hashed_address = keccak256(address);
         let hashed_slot = keccak256(slot);
-        if let Some(mut value) = self
-            .state
-            .storage_tries
-            .get(&hashed_address)
-            .and_then(|storage_trie| storage_trie.get(hashed_slot.as_slice()).unwrap())
-        {
+        let value = match self.state.storage_tries.get(&hashed_address) {
+            Some(storage_trie) => storage_trie
+                .get(hashed_slot.as_slice())
+                .map_err(|err| ProviderError::TrieWitnessError(err.to_string()))?,
+            None => None,
+        };
+
+        if let Some(mut value) = value {
             Ok(U256::decode(&mut value)?)
         } else {
             Ok(U256::ZERO)
@@ -1,6 +1,7 @@
 #![cfg_attr(not(test), warn(unused_crate_dependencies))]
 
 use alloy_consensus::Header;
+use alloy_primitives::B256;
 use alloy_provider::Network;
 use async_trait::async_trait;
 use mpt::EthereumState;
@@ -8,7 +9,10 @@ use revm_database::{BundleState, DatabaseRef};
 use revm_state::Bytecode;
 
 mod basic;
-pub use basic::BasicRpcDb;
+pub use basic::{BasicRpcDb, FetchConfig, DEFAULT_MAX_CONCURRENT_REQUESTS};
+
+mod cache;
+pub use cache::{RpcCache, DEFAULT_RPC_CACHE_SIZE};
 
 #[cfg(feature = "execution-witness")]
 mod execution_witness;
@@ -16,7 +20,7 @@ mod execution_witness;
 pub use execution_witness::ExecutionWitnessRpcDb;
 
 mod error;
-pub use error::RpcDbError;
+pub use error::{ProviderRole, RpcDbError};
 
 #[async_trait]
 pub trait RpcDb<N: Network>: DatabaseRef {
@@ -27,4 +31,11 @@ pub trait RpcDb<N: Network>: DatabaseRef {
 
     // Fetches the parent headers needed to constrain the BLOCKHASH opcode.
     async fn ancestor_headers(&self) -> Result<Vec<Header>, RpcDbError>;
+
+    /// Prefetches the state accessed by a block, once the previous blocks have been executed.
+    /// Returns the number of prefetched accounts, the databases without prefetch fetching the
+    /// state lazily.
+    async fn prefetch(&self, _block_number: u64, _block_hash: B256) -> Result<usize, RpcDbError> {
+        Ok(0)
+    }
 }